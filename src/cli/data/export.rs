@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{loader, CliCommand, CommandContext};
+use crate::devtool::DEFAULT_ENV_ID;
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Export {
+    /// The archive file to create
+    #[clap(parse(from_os_str))]
+    file: PathBuf,
+
+    /// Also export the devtool Postgres data (users, environments...)
+    #[clap(long, action)]
+    devtool: bool,
+
+    /// The devtool app environment whose Mongo data is used
+    #[clap(long, default_value_t = DEFAULT_ENV_ID)]
+    env_id: u64,
+}
+
+#[async_trait]
+impl CliCommand for Export {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        loader(
+            "Export the databases data...",
+            &format!("Databases data exported to {}", self.file.display()),
+            "Failed exporting the databases data",
+            !context.verbose,
+            || async { lenra::export_data(context, &self.file, self.devtool, self.env_id).await },
+        )
+        .await
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{loader, CliCommand, CommandContext};
+use crate::devtool::DEFAULT_ENV_ID;
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Import {
+    /// The archive file created by the export command
+    #[clap(parse(from_os_str))]
+    file: PathBuf,
+
+    /// Replace the data without asking for confirmation
+    #[clap(short, long, action)]
+    yes: bool,
+
+    /// The devtool app environment whose Mongo data is used
+    #[clap(long, default_value_t = DEFAULT_ENV_ID)]
+    env_id: u64,
+}
+
+#[async_trait]
+impl CliCommand for Import {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        if !self.yes && !lenra::confirm("Replace the databases data ? [y/N] ")? {
+            println!("Data import canceled");
+            return Ok(());
+        }
+        let warnings = loader(
+            "Import the databases data...",
+            "Databases data imported",
+            "Failed importing the databases data",
+            !context.verbose,
+            || async { lenra::import_data(context, &self.file, self.env_id).await },
+        )
+        .await?;
+        lenra::display_warnings(&warnings);
+        Ok(())
+    }
+}
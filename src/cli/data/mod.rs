@@ -0,0 +1,49 @@
+//! # data
+//!
+//! The data subcommands manage the app environment databases data
+
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+use crate::cli::CliCommand;
+use crate::errors::Result;
+
+use self::{export::Export, import::Import, reset::Reset, snapshot::Snapshot};
+
+use super::CommandContext;
+
+mod export;
+mod import;
+mod reset;
+mod snapshot;
+
+#[derive(Args, Debug, Clone)]
+pub struct Data {
+    #[clap(subcommand)]
+    command: DataCommand,
+}
+
+/// The data subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum DataCommand {
+    /// Remove the databases data
+    Reset(Reset),
+    /// Export the databases data to an archive file
+    Export(Export),
+    /// Import the databases data from an archive file
+    Import(Import),
+    /// Manage named snapshots of the databases data
+    Snapshot(Snapshot),
+}
+
+#[async_trait]
+impl CliCommand for Data {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        match &self.command {
+            DataCommand::Reset(reset) => reset.run(context).await,
+            DataCommand::Export(export) => export.run(context).await,
+            DataCommand::Import(import) => import.run(context).await,
+            DataCommand::Snapshot(snapshot) => snapshot.run(context).await,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{loader, CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Reset {
+    /// Remove the data without asking for confirmation
+    #[clap(short, long, action)]
+    yes: bool,
+}
+
+#[async_trait]
+impl CliCommand for Reset {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        if !self.yes && !lenra::confirm("Remove the databases data ? [y/N] ")? {
+            println!("Data reset canceled");
+            return Ok(());
+        }
+        loader(
+            "Reset the databases data...",
+            "Databases data reset",
+            "Failed resetting the databases data",
+            !context.verbose,
+            || async { lenra::reset_data(context).await },
+        )
+        .await
+    }
+}
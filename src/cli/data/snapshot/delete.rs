@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Delete {
+    /// The names of the snapshots to delete
+    #[clap(required = true)]
+    names: Vec<String>,
+}
+
+#[async_trait]
+impl CliCommand for Delete {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        lenra::delete_snapshots(context, &self.names)?;
+        println!("Deleted snapshots: {}", self.names.join(", "));
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct List;
+
+#[async_trait]
+impl CliCommand for List {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let snapshots = lenra::list_snapshots(context)?;
+        lenra::display_snapshots(&snapshots);
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+//! # snapshot
+//!
+//! The snapshot subcommands manage named databases data snapshots stored in the `.lenra/snapshots` directory
+
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+
+use self::{delete::Delete, list::List, restore::Restore, save::Save};
+
+mod delete;
+mod list;
+mod restore;
+mod save;
+
+#[derive(Args, Debug, Clone)]
+pub struct Snapshot {
+    #[clap(subcommand)]
+    command: SnapshotCommand,
+}
+
+/// The snapshot subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum SnapshotCommand {
+    /// Save the databases data as a named snapshot
+    Save(Save),
+    /// List the saved snapshots
+    List(List),
+    /// Replace the databases data by the ones of a snapshot
+    Restore(Restore),
+    /// Delete snapshots
+    Delete(Delete),
+}
+
+#[async_trait]
+impl CliCommand for Snapshot {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        match &self.command {
+            SnapshotCommand::Save(save) => save.run(context).await,
+            SnapshotCommand::List(list) => list.run(context).await,
+            SnapshotCommand::Restore(restore) => restore.run(context).await,
+            SnapshotCommand::Delete(delete) => delete.run(context).await,
+        }
+    }
+}
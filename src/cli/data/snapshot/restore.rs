@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{loader, CliCommand, CommandContext};
+use crate::devtool::DEFAULT_ENV_ID;
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Restore {
+    /// The snapshot name
+    name: String,
+
+    /// Replace the data without asking for confirmation
+    #[clap(short, long, action)]
+    yes: bool,
+
+    /// The devtool app environment whose Mongo data is used
+    #[clap(long, default_value_t = DEFAULT_ENV_ID)]
+    env_id: u64,
+}
+
+#[async_trait]
+impl CliCommand for Restore {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        if !self.yes
+            && !lenra::confirm(&format!(
+                "Replace the databases data by the {} snapshot ? [y/N] ",
+                self.name
+            ))?
+        {
+            println!("Snapshot restore canceled");
+            return Ok(());
+        }
+        let warnings = loader(
+            &format!("Restore the {} snapshot...", self.name),
+            &format!("Snapshot {} restored", self.name),
+            &format!("Failed restoring the {} snapshot", self.name),
+            !context.verbose,
+            || async { lenra::restore_snapshot(context, &self.name, self.env_id).await },
+        )
+        .await?;
+        lenra::display_warnings(&warnings);
+        Ok(())
+    }
+}
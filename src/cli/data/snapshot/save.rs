@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{loader, CliCommand, CommandContext};
+use crate::devtool::DEFAULT_ENV_ID;
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Save {
+    /// The snapshot name
+    name: String,
+
+    /// Replace the snapshot if it already exists
+    #[clap(short, long, action)]
+    force: bool,
+
+    /// The devtool app environment whose Mongo data is used
+    #[clap(long, default_value_t = DEFAULT_ENV_ID)]
+    env_id: u64,
+}
+
+#[async_trait]
+impl CliCommand for Save {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        loader(
+            &format!("Save the {} snapshot...", self.name),
+            &format!("Snapshot {} saved", self.name),
+            &format!("Failed saving the {} snapshot", self.name),
+            !context.verbose,
+            || async { lenra::save_snapshot(context, &self.name, self.force, self.env_id).await },
+        )
+        .await
+    }
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::db::Database;
+use crate::devtool::DEFAULT_ENV_ID;
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Db {
+    /// The database: mongo for the app data or postgres for the devtool data
+    #[clap(value_enum)]
+    pub database: Database,
+
+    /// Run a query and print its result as JSON instead of opening an interactive shell
+    #[clap(long)]
+    pub eval: Option<String>,
+
+    /// The devtool app environment whose Mongo database is used
+    #[clap(long, default_value_t = DEFAULT_ENV_ID)]
+    pub env_id: u64,
+}
+
+#[async_trait]
+impl CliCommand for Db {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        lenra::db_shell(context, self.database, self.env_id, self.eval.as_deref()).await
+    }
+}
@@ -76,7 +76,7 @@ impl KeyboardShorcut<TerminalCommand> for InteractiveCommand {
                 ..Default::default()
             })),
             InteractiveCommand::Quit => Some(TerminalCommand::Exit),
-            InteractiveCommand::Stop => Some(TerminalCommand::Stop(Stop)),
+            InteractiveCommand::Stop => Some(TerminalCommand::Stop(Stop::default())),
         }
     }
 }
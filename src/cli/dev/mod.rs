@@ -1,3 +1,5 @@
+use std::{future::Future, pin::Pin};
+
 use async_trait::async_trait;
 use chrono::{DateTime, SecondsFormat, Utc};
 pub use clap::Args;
@@ -18,14 +20,22 @@ use crate::{
 };
 
 use interactive::{InteractiveCommand, KeyboardShorcut};
+use watch::DevWatcher;
+
+type InteractiveListener = Pin<Box<dyn Future<Output = Result<Option<TerminalCommand>>> + Send>>;
 
 mod interactive;
+mod watch;
 
 #[derive(Args, Debug, Clone)]
 pub struct Dev {
     /// Attach the dev mode without rebuilding the app and restarting it.
     #[clap(long, action)]
     pub attach: bool,
+
+    /// Reload the app when its files change.
+    #[clap(short, long, action)]
+    pub watch: bool,
 }
 
 #[async_trait]
@@ -47,9 +57,12 @@ impl CliCommand for Dev {
         };
         let mut last_logs: Option<DateTime<Utc>> = None;
 
-        lenra::display_app_access_url();
+        let mut watcher = DevWatcher::new(context, self.watch)?;
+
+        lenra::display_app_access_url(context);
         InteractiveCommand::Help.to_value();
         let mut interactive_cmd = None;
+        let mut interactive_listener = None;
         loop {
             if let Some(command) = interactive_cmd {
                 let keep_running = run_command(&command, context).await;
@@ -58,7 +71,14 @@ impl CliCommand for Dev {
                 }
             }
             let end_date;
-            (end_date, interactive_cmd) = run_logs(&previous_log, last_logs, context).await?;
+            (end_date, interactive_cmd) = run_logs(
+                &previous_log,
+                last_logs,
+                &mut interactive_listener,
+                &mut watcher,
+                context,
+            )
+            .await?;
             last_logs = Some(end_date);
         }
 
@@ -70,6 +90,8 @@ impl CliCommand for Dev {
 async fn run_logs(
     logs: &Logs,
     last_end: Option<DateTime<Utc>>,
+    interactive_listener: &mut Option<InteractiveListener>,
+    watcher: &mut DevWatcher,
     context: &mut CommandContext,
 ) -> Result<(DateTime<Utc>, Option<TerminalCommand>)> {
     let mut clone = logs.clone();
@@ -77,10 +99,14 @@ async fn run_logs(
         // Only displays new logs
         clone.since = Some(last_logs.to_rfc3339_opts(SecondsFormat::Secs, true));
     }
+    // The listener is kept between log sessions until a key is pressed to avoid reading the user input twice
+    let listener =
+        interactive_listener.get_or_insert_with(|| Box::pin(listen_interactive_command()));
 
     let command = select! {
-        res = listen_interactive_command() => {res?}
+        res = listener.as_mut() => {*interactive_listener = None; res?}
         res = clone.run(context) => {res?; None}
+        res = watcher.next_command() => {Some(res?)}
         // res = tokio::signal::ctrl_c() => {res?; None}
     };
     Ok((Utc::now(), command))
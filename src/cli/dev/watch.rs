@@ -0,0 +1,160 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use colored::{Color, Colorize};
+
+use crate::{
+    cli::{
+        refresh::Refresh, reload::Reload, restart::Restart, terminal::TerminalCommand,
+        CommandContext,
+    },
+    config::DOCKERIGNORE_DEFAULT_PATH,
+    errors::Result,
+    watcher::{FileWatcher, IgnoreRules},
+};
+
+/// Watches the app files to restart, refresh or reload the app when they change
+pub struct DevWatcher {
+    watcher: FileWatcher,
+    config_path: PathBuf,
+    /// The app workdir when the whole app is watched
+    workdir: Option<PathBuf>,
+    mount_sources: Vec<PathBuf>,
+    dockerignore: PathBuf,
+}
+
+impl DevWatcher {
+    /// Creates a watcher for the config file, the dev mounts sources and the app workdir if needed
+    pub fn new(context: &CommandContext, watch_workdir: bool) -> Result<Self> {
+        let mount_sources: Vec<PathBuf> = context
+            .config
+            .as_ref()
+            .and_then(|conf| conf.dev.as_ref())
+            .and_then(|dev| dev.mounts.clone())
+            .unwrap_or_default()
+            .iter()
+            .map(|mount| context.resolve_path(&mount.source))
+            .collect();
+        let workdir = if watch_workdir {
+            Some(context.resolve_path(&PathBuf::from(".")))
+        } else {
+            None
+        };
+
+        let mut dockerignore = context.resolve_path(&DOCKERIGNORE_DEFAULT_PATH.iter().collect());
+        if !dockerignore.exists() {
+            dockerignore = context.resolve_path(&PathBuf::from(".dockerignore"));
+        }
+        let config_path = context
+            .config_path
+            .canonicalize()
+            .unwrap_or(context.config_path.clone());
+        // the mount sources are watched recursively and the workdir directory by directory to skip the ignored ones
+        let mut watcher = FileWatcher::new(&mount_sources)?;
+        if let Some(workdir) = &workdir {
+            let rules = Arc::new(IgnoreRules::new(workdir, Some(&dockerignore)));
+            watcher.watch_tree(workdir, rules, &mount_sources)?;
+        }
+        if !mount_sources
+            .iter()
+            .any(|path| config_path.starts_with(path))
+        {
+            watcher.watch_file(&config_path)?;
+        }
+        Ok(DevWatcher {
+            watcher,
+            config_path,
+            workdir,
+            mount_sources,
+            dockerignore,
+        })
+    }
+
+    /// Waits for relevant changes and returns the command to apply them.
+    /// Changes only in the mounted sources restart the app, changes only in the config file refresh the environment and others reload it.
+    pub async fn next_command(&mut self) -> Result<TerminalCommand> {
+        loop {
+            let paths = self.watcher.next_changes().await?;
+            let config_changed = paths.contains(&self.config_path);
+            let is_mounted = |path: &PathBuf| {
+                self.mount_sources
+                    .iter()
+                    .any(|source| path.starts_with(source))
+            };
+            // the ignore rules are loaded each time since a reload can regenerate them
+            let rules = self
+                .workdir
+                .as_ref()
+                .map(|workdir| Arc::new(IgnoreRules::new(workdir, Some(&self.dockerignore))));
+            // the created directories of the workdir are watched too
+            if let (Some(workdir), Some(rules)) = (&self.workdir, &rules) {
+                let created_dirs: Vec<&PathBuf> = paths
+                    .iter()
+                    .filter(|path| {
+                        path.starts_with(workdir)
+                            && path.is_dir()
+                            && !is_mounted(path)
+                            && !self.watcher.is_tree_dir(path)
+                            && !rules.is_ignored(path)
+                    })
+                    .collect();
+                for path in created_dirs {
+                    self.watcher
+                        .watch_tree(path, rules.clone(), &self.mount_sources)?;
+                }
+            }
+            let paths: Vec<PathBuf> = paths
+                .into_iter()
+                .filter(|path| path != &self.config_path)
+                .filter(|path| {
+                    is_mounted(path)
+                        || rules
+                            .as_ref()
+                            .map(|rules| !rules.is_ignored(path))
+                            .unwrap_or(false)
+                })
+                .collect();
+            if paths.is_empty() {
+                if config_changed {
+                    println!(
+                        "\n{} refreshing the app environment",
+                        "Config file changed,".color(Color::Yellow)
+                    );
+                    return Ok(TerminalCommand::Refresh(Refresh));
+                }
+                continue;
+            }
+
+            let (message, command) = if !config_changed && paths.iter().all(is_mounted) {
+                ("restarting the app", TerminalCommand::Restart(Restart))
+            } else {
+                ("reloading the app", TerminalCommand::Reload(Reload))
+            };
+            let trigger = match paths.len() {
+                1 => self.display_path(&paths[0]),
+                len => format!(
+                    "{} and {} other files",
+                    self.display_path(&paths[0]),
+                    len - 1
+                ),
+            };
+            println!(
+                "\n{} {}",
+                format!("{} changed,", trigger).color(Color::Yellow),
+                message
+            );
+            return Ok(command);
+        }
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        self.workdir
+            .as_ref()
+            .and_then(|workdir| path.strip_prefix(workdir).ok())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
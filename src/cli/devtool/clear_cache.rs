@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{loader, CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct ClearCache;
+
+#[async_trait]
+impl CliCommand for ClearCache {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        loader(
+            "Clearing the devtool caches...",
+            "Devtool caches cleared",
+            "Failed clearing the devtool caches",
+            !context.verbose,
+            || async { lenra::clear_devtool_caches(context).await },
+        )
+        .await
+    }
+}
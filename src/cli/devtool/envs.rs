@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Envs;
+
+#[async_trait]
+impl CliCommand for Envs {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let env_ids = lenra::list_devtool_envs(context).await?;
+        if env_ids.is_empty() {
+            println!("No active app environment");
+        }
+        for env_id in env_ids {
+            println!("{}", env_id);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::devtool;
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct LogLevel {
+    /// The new log level
+    #[clap(value_enum)]
+    level: devtool::LogLevel,
+}
+
+#[async_trait]
+impl CliCommand for LogLevel {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        lenra::set_devtool_log_level(context, self.level).await?;
+        println!("Devtool log level set to {:?}", self.level);
+        Ok(())
+    }
+}
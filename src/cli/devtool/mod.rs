@@ -0,0 +1,53 @@
+//! # devtool
+//!
+//! The devtool subcommands manage the running devtool through remote procedure calls
+
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+
+use self::{
+    clear_cache::ClearCache, envs::Envs, log_level::LogLevel, restart::Restart, sessions::Sessions,
+};
+
+mod clear_cache;
+mod envs;
+mod log_level;
+mod restart;
+mod sessions;
+
+#[derive(Args, Debug, Clone)]
+pub struct Devtool {
+    #[clap(subcommand)]
+    command: DevtoolCommand,
+}
+
+/// The devtool subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum DevtoolCommand {
+    /// Restart an app environment with a fresh state
+    Restart(Restart),
+    /// List the active app environments
+    Envs(Envs),
+    /// List the active client sessions
+    Sessions(Sessions),
+    /// Clear the caches of all the active app environments
+    ClearCache(ClearCache),
+    /// Change the devtool log level until its restart
+    LogLevel(LogLevel),
+}
+
+#[async_trait]
+impl CliCommand for Devtool {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        match &self.command {
+            DevtoolCommand::Restart(restart) => restart.run(context).await,
+            DevtoolCommand::Envs(envs) => envs.run(context).await,
+            DevtoolCommand::Sessions(sessions) => sessions.run(context).await,
+            DevtoolCommand::ClearCache(clear_cache) => clear_cache.run(context).await,
+            DevtoolCommand::LogLevel(log_level) => log_level.run(context).await,
+        }
+    }
+}
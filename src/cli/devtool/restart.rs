@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{loader, CliCommand, CommandContext};
+use crate::devtool::DEFAULT_ENV_ID;
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Restart {
+    /// The id of the app environment
+    #[clap(long, default_value_t = DEFAULT_ENV_ID)]
+    env_id: u64,
+}
+
+#[async_trait]
+impl CliCommand for Restart {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        loader(
+            &format!("Restart the app environment {}...", self.env_id),
+            &format!("App environment {} restarted", self.env_id),
+            &format!("Failed restarting the app environment {}", self.env_id),
+            !context.verbose,
+            || async { lenra::restart_devtool_env(context, self.env_id).await },
+        )
+        .await
+    }
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Sessions;
+
+#[async_trait]
+impl CliCommand for Sessions {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let session_ids = lenra::list_devtool_sessions(context).await?;
+        if session_ids.is_empty() {
+            println!("No active session");
+        }
+        for session_id in session_ids {
+            println!("{}", session_id);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+//! # eject
+//!
+//! The eject subcommand replaces the Dofigen generator by the Dockerfile it generates
+
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::CliCommand;
+use crate::errors::Result;
+use crate::{cli, lenra};
+
+use super::CommandContext;
+
+#[derive(Args, Debug, Clone)]
+pub struct Eject {
+    /// The path of the Dockerfile to create, relative to the app path
+    #[clap(long, parse(from_os_str), default_value = "Dockerfile")]
+    pub dockerfile: std::path::PathBuf,
+
+    /// Display the changes without applying them
+    #[clap(long, action)]
+    pub dry_run: bool,
+
+    /// Override the Dockerfile if it already exists
+    #[clap(short, long, action)]
+    pub force: bool,
+}
+
+#[async_trait]
+impl CliCommand for Eject {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let warnings = if self.dry_run {
+            lenra::eject_app(context, &self.dockerfile, true, self.force).await?
+        } else {
+            cli::loader(
+                "Ejecting Dofigen configuration...",
+                "Dofigen configuration ejected",
+                "Failed ejecting Dofigen configuration",
+                !context.verbose,
+                || async { lenra::eject_app(context, &self.dockerfile, false, self.force).await },
+            )
+            .await?
+        };
+        lenra::display_warnings(&warnings);
+        Ok(())
+    }
+}
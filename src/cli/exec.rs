@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::docker_compose::{ExecOptions, Service};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Exec {
+    /// The service: app, devtool, postgres, mongo or a dev service name
+    #[clap(value_parser)]
+    pub service: Service,
+
+    /// The command to run, a shell being opened when it is not set
+    #[clap(last = true)]
+    pub command: Vec<String>,
+
+    /// Set environment variables (e.g. -e LOG_LEVEL=debug)
+    #[clap(short, long = "env", value_parser = parse_env)]
+    pub envs: Vec<String>,
+
+    /// Run the command as this user
+    #[clap(short, long)]
+    pub user: Option<String>,
+
+    /// Disable the pseudo-TTY allocation
+    #[clap(short = 'T', long, action)]
+    pub no_tty: bool,
+}
+
+fn parse_env(env: &str) -> std::result::Result<String, String> {
+    match env.split_once('=') {
+        Some((key, _)) if !key.is_empty() => Ok(env.to_string()),
+        _ => Err("the environment variable must be formatted as KEY=VALUE".into()),
+    }
+}
+
+#[async_trait]
+impl CliCommand for Exec {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let options = ExecOptions {
+            envs: self.envs.clone(),
+            user: self.user.clone(),
+            no_tty: self.no_tty,
+        };
+        lenra::exec_service(context, self.service.clone(), &self.command, &options).await
+    }
+}
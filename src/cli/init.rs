@@ -0,0 +1,50 @@
+//! # init
+//!
+//! The init subcommand creates the Lenra configuration of an existing project
+
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::CliCommand;
+use crate::config::config_to_yaml;
+use crate::errors::{Error, Result};
+use crate::{cli, lenra};
+
+use super::CommandContext;
+
+#[derive(Args, Debug, Clone)]
+pub struct Init {
+    /// The project path
+    #[clap(short, long, parse(from_os_str), default_value = ".")]
+    path: std::path::PathBuf,
+
+    /// Create the configuration without asking for confirmation
+    #[clap(short, long, action)]
+    yes: bool,
+}
+
+#[async_trait]
+impl CliCommand for Init {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let config_path = self.path.join(&context.config_path);
+        if config_path.exists() {
+            return Err(Error::ConfigFileAlreadyExists(config_path));
+        }
+
+        let app = lenra::propose_app_config(&self.path)?;
+        println!("Proposed configuration:\n\n{}", config_to_yaml(&app)?);
+        if !self.yes && !lenra::confirm("Create the configuration ? [y/N] ")? {
+            println!("Initialization canceled");
+            return Ok(());
+        }
+
+        cli::loader(
+            "Initializing project...",
+            "Project initialized",
+            "Failed initializing project",
+            !context.verbose,
+            || async { lenra::init_project(&self.path, &config_path, &app) },
+        )
+        .await
+    }
+}
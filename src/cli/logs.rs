@@ -5,6 +5,7 @@ use crate::cli::CliCommand;
 use crate::command::run_command;
 use crate::docker_compose::{create_compose_command, Service};
 use crate::errors::Result;
+use crate::lenra;
 
 use super::CommandContext;
 
@@ -38,8 +39,8 @@ pub struct Logs {
     #[clap(long)]
     pub until: Option<String>,
 
-    /// The logged service list
-    #[clap(value_enum, default_value = "app")]
+    /// The logged service list: app, devtool, postgres, mongo or a dev service name
+    #[clap(value_parser, default_value = "app")]
     pub services: Vec<Service>,
 }
 
@@ -47,6 +48,7 @@ pub struct Logs {
 impl CliCommand for Logs {
     async fn run(&self, context: &mut CommandContext) -> Result<()> {
         log::info!("Show logs");
+        lenra::check_services(context, &self.services)?;
 
         let mut command = create_compose_command(context);
 
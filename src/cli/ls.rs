@@ -0,0 +1,54 @@
+//! # ls
+//!
+//! The ls subcommand lists the Lenra environments of the machine
+
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::CliCommand;
+use crate::errors::Result;
+use crate::{cli, lenra};
+
+use super::CommandContext;
+
+#[derive(Args, Debug, Clone)]
+pub struct Ls {
+    /// Stop and remove all the Lenra environments
+    #[clap(long, action, conflicts_with = "prune")]
+    pub stop_all: bool,
+
+    /// Stop and remove the stopped environments and those whose project has been removed
+    #[clap(long, action)]
+    pub prune: bool,
+}
+
+#[async_trait]
+impl CliCommand for Ls {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let mut environments = lenra::list_environments().await?;
+        if !self.stop_all && !self.prune {
+            lenra::display_environments(&environments);
+            return Ok(());
+        }
+
+        if self.prune {
+            environments.retain(|environment| environment.is_stale());
+        }
+        if environments.is_empty() {
+            println!("No Lenra environment to stop");
+            return Ok(());
+        }
+        cli::loader(
+            "Stopping Lenra environments...",
+            "Lenra environments stopped",
+            "Failed stopping Lenra environments",
+            !context.verbose,
+            || async { lenra::stop_environments(&environments, context.verbose).await },
+        )
+        .await
+    }
+
+    fn need_config(&self) -> bool {
+        false
+    }
+}
@@ -8,26 +8,37 @@ use log::debug;
 
 use crate::{
     config::{load_config_file, Application, DEFAULT_CONFIG_FILE},
-    docker_compose::Service,
+    docker_compose::ExposedService,
     errors::Result,
 };
 
 use self::{
-    build::Build, dev::Dev, logs::Logs, new::New, reload::Reload, start::Start, stop::Stop,
-    update::Update, upgrade::Upgrade,
+    build::Build, data::Data, db::Db, dev::Dev, devtool::Devtool, eject::Eject, exec::Exec,
+    init::Init, logs::Logs, ls::Ls, new::New, refresh::Refresh, reload::Reload, restart::Restart,
+    start::Start, stop::Stop, update::Update, upgrade::Upgrade, users::Users,
 };
 
 mod build;
 // mod check;
+mod data;
+mod db;
 mod dev;
+mod devtool;
+mod eject;
+mod exec;
+mod init;
 mod logs;
+mod ls;
 mod new;
+mod refresh;
 mod reload;
+mod restart;
 mod start;
 mod stop;
 pub mod terminal;
 mod update;
 mod upgrade;
+mod users;
 
 /// The Lenra command line interface
 #[derive(Parser, Debug, Clone)]
@@ -40,9 +51,9 @@ pub struct Cli {
     #[clap(global=true, parse(from_os_str), long, default_value = DEFAULT_CONFIG_FILE)]
     pub config: std::path::PathBuf,
 
-    /// Exposes services ports.
-    #[clap(global=true, long, value_enum, default_values = &[], default_missing_values = &["app", "postgres", "mongo"])]
-    pub expose: Vec<Service>,
+    /// Exposes services ports: app, devtool, postgres, mongo or a dev service name, optionally with a host port binding (e.g. app=18080).
+    #[clap(global=true, long, value_parser, default_values = &[], default_missing_values = &["app", "postgres", "mongo"])]
+    pub expose: Vec<ExposedService>,
 
     /// Run the commands as verbose.
     #[clap(global = true, short, long, action)]
@@ -62,6 +73,8 @@ pub trait CliCommand {
 pub enum Command {
     /// Create a new Lenra app project from a template
     New(New),
+    /// Create the Lenra configuration of an existing project
+    Init(Init),
     /// Build your app in release mode
     Build(Build),
     /// Start your app previously built with the build command
@@ -70,6 +83,18 @@ pub enum Command {
     Logs(Logs),
     /// Stop your app previously started with the start command
     Stop(Stop),
+    /// List the Lenra environments of the machine
+    Ls(Ls),
+    /// Manage the databases data
+    Data(Data),
+    /// Open a shell on a database of the environment
+    Db(Db),
+    /// Execute a command in a service of the environment
+    Exec(Exec),
+    /// Manage the running devtool
+    Devtool(Devtool),
+    /// Manage the simulated users of the devtool
+    Users(Users),
     /// Start the app in an interactive mode
     Dev(Dev),
     /// Upgrade the app with the last template updates
@@ -80,6 +105,12 @@ pub enum Command {
     // Check(Check),
     /// Reload the app by rebuilding and restarting it
     Reload(Reload),
+    /// Restart the app without rebuilding it
+    Restart(Restart),
+    /// Apply the config changes by recreating only the affected services
+    Refresh(Refresh),
+    /// Replace the Dofigen generator by the Dockerfile it generates
+    Eject(Eject),
 }
 
 #[async_trait]
@@ -91,22 +122,32 @@ impl CliCommand for Command {
         }
         match self {
             Command::New(new) => new.run(context),
+            Command::Init(init) => init.run(context),
             Command::Build(build) => build.run(context),
             Command::Start(start) => start.run(context),
             Command::Logs(logs) => logs.run(context),
             Command::Stop(stop) => stop.run(context),
+            Command::Ls(ls) => ls.run(context),
+            Command::Data(data) => data.run(context),
+            Command::Db(db) => db.run(context),
+            Command::Exec(exec) => exec.run(context),
+            Command::Devtool(devtool) => devtool.run(context),
+            Command::Users(users) => users.run(context),
             Command::Dev(dev) => dev.run(context),
             Command::Upgrade(upgrade) => upgrade.run(context),
             Command::Update(update) => update.run(context),
             // Command::Check(check) => check.run(context),
             Command::Reload(reload) => reload.run(context),
+            Command::Restart(restart) => restart.run(context),
+            Command::Refresh(refresh) => refresh.run(context),
+            Command::Eject(eject) => eject.run(context),
         }
         .await
     }
 
     fn need_config(&self) -> bool {
         match self {
-            Command::New(_) => false,
+            Command::New(_) | Command::Init(_) | Command::Ls(_) => false,
             _ => true,
         }
     }
@@ -121,7 +162,7 @@ pub struct CommandContext {
     pub config: Option<Application>,
 
     /// Exposes all services ports.
-    pub expose: Vec<Service>,
+    pub expose: Vec<ExposedService>,
 
     /// Run command as verbose.
     pub verbose: bool,
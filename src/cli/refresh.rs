@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::CliCommand;
+use crate::errors::Result;
+use crate::lenra;
+
+use super::{
+    build::{build_loader, generate_app_env_loader},
+    start::{clear_cache_loader, start_loader},
+    CommandContext,
+};
+
+#[derive(Args, Default, Debug, Clone)]
+pub struct Refresh;
+
+#[async_trait]
+impl CliCommand for Refresh {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let previous_dockerfile = lenra::read_generated_dockerfile(context);
+        generate_app_env_loader(context, false).await?;
+        // the app is only rebuilt when its Dockerfile changed
+        if lenra::read_generated_dockerfile(context) != previous_dockerfile {
+            build_loader(context).await?;
+        }
+        // only the services whose configuration changed are recreated
+        start_loader(context).await?;
+        clear_cache_loader(context).await
+    }
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::CliCommand;
+use crate::errors::Result;
+use crate::lenra;
+
+use super::{loader, start::clear_cache_loader, CommandContext};
+
+#[derive(Args, Default, Debug, Clone)]
+pub struct Restart;
+
+#[async_trait]
+impl CliCommand for Restart {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        restart_loader(context).await?;
+        clear_cache_loader(context).await
+    }
+}
+
+pub async fn restart_loader(context: &mut CommandContext) -> Result<()> {
+    loader(
+        "Restart app...",
+        "App restarted",
+        "Failed restarting app",
+        !context.verbose,
+        || async { lenra::restart_app(context).await },
+    )
+    .await
+}
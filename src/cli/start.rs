@@ -15,7 +15,7 @@ impl CliCommand for Start {
     async fn run(&self, context: &mut CommandContext) -> Result<()> {
         start_loader(context).await?;
         clear_cache_loader(context).await?;
-        lenra::display_app_access_url();
+        lenra::display_app_access_url(context);
         Ok(())
     }
 }
@@ -7,23 +7,27 @@ use crate::lenra;
 
 use super::{loader, CommandContext};
 
-#[derive(Args, Debug, Clone)]
-pub struct Stop;
+#[derive(Args, Default, Debug, Clone)]
+pub struct Stop {
+    /// Remove the databases data
+    #[clap(long, action)]
+    pub reset: bool,
+}
 
 #[async_trait]
 impl CliCommand for Stop {
     async fn run(&self, context: &mut CommandContext) -> Result<()> {
-        stop_loader(context).await
+        stop_loader(context, self.reset).await
     }
 }
 
-pub async fn stop_loader(context: &mut CommandContext) -> Result<()> {
+pub async fn stop_loader(context: &mut CommandContext, reset_data: bool) -> Result<()> {
     loader(
         "Stop app environment...",
         "App environment stopped",
         "Failed stopping app environment",
         !context.verbose,
-        || async { lenra::stop_env(context).await },
+        || async { lenra::stop_env(context, reset_data).await },
     )
     .await
 }
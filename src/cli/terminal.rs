@@ -9,15 +9,16 @@ use log::{debug, warn};
 use rustyline::{error::ReadlineError, Editor};
 
 use crate::{
-    docker_compose::Service,
+    docker_compose::ExposedService,
     errors::{Error, Result},
 };
 
-use crate::cli::{logs::Logs, CliCommand};
+use crate::cli::{logs::Logs, ls::Ls, CliCommand};
 
 use super::{
-    build::Build, dev::Dev, reload::Reload, start::Start, stop::Stop, update::Update,
-    upgrade::Upgrade, CommandContext,
+    build::Build, data::Data, db::Db, dev::Dev, devtool::Devtool, exec::Exec, refresh::Refresh,
+    reload::Reload, restart::Restart, start::Start, stop::Stop, update::Update, upgrade::Upgrade,
+    users::Users, CommandContext,
 };
 
 const LENRA_COMMAND: &str = "lenra";
@@ -98,12 +99,22 @@ pub async fn run_command(command: &TerminalCommand, context: &mut CommandContext
 }
 
 fn parse_command_line(line: String) -> Result<TerminalCli, clap::Error> {
-    let args = &mut line.split_whitespace().collect::<Vec<&str>>();
+    // the arguments are split like in a shell to handle the quoted ones
+    let args = &mut shlex::split(&line).ok_or_else(|| {
+        clap::Error::raw(
+            clap::ErrorKind::InvalidValue,
+            "The command line contains an unclosed quote\n",
+        )
+    })?;
 
-    let first_arg = if args.len() > 0 { Some(args[0]) } else { None };
+    let first_arg = if args.len() > 0 {
+        Some(args[0].clone())
+    } else {
+        None
+    };
     if let Some(arg) = first_arg {
         if LENRA_COMMAND != arg {
-            args.push(LENRA_COMMAND);
+            args.push(LENRA_COMMAND.to_string());
             args.rotate_right(1);
         }
     }
@@ -141,6 +152,18 @@ pub enum TerminalCommand {
     Logs(Logs),
     /// Stop your app previously started with the start command
     Stop(Stop),
+    /// List the Lenra environments of the machine
+    Ls(Ls),
+    /// Manage the databases data
+    Data(Data),
+    /// Open a shell on a database of the environment
+    Db(Db),
+    /// Execute a command in a service of the environment
+    Exec(Exec),
+    /// Manage the running devtool
+    Devtool(Devtool),
+    /// Manage the simulated users of the devtool
+    Users(Users),
     /// Start the app in an interactive mode
     Dev(Dev),
     /// Upgrade the app with the last template updates
@@ -151,6 +174,10 @@ pub enum TerminalCommand {
     // Check(Check),
     /// Reload the app by rebuilding and restarting it
     Reload(Reload),
+    /// Restart the app without rebuilding it
+    Restart(Restart),
+    /// Apply the config changes by recreating only the affected services
+    Refresh(Refresh),
     /// Exits the terminal
     Exit,
     /// Exposes the app ports
@@ -159,9 +186,9 @@ pub enum TerminalCommand {
 
 #[derive(Args, Clone, Debug)]
 pub struct Expose {
-    /// The service list to expose
-    #[clap(value_enum, default_values = &["app", "postgres", "mongo"])]
-    pub services: Vec<Service>,
+    /// The service list to expose: app, devtool, postgres, mongo or a dev service name, optionally with a host port binding (e.g. app=18080)
+    #[clap(value_parser, default_values = &["app", "postgres", "mongo"])]
+    pub services: Vec<ExposedService>,
 }
 
 impl TerminalCommand {
@@ -182,11 +209,19 @@ impl TerminalCommand {
             TerminalCommand::Start(start) => start.run(context).await?,
             TerminalCommand::Logs(logs) => logs.run(context).await?,
             TerminalCommand::Stop(stop) => stop.run(context).await?,
+            TerminalCommand::Ls(ls) => ls.run(context).await?,
+            TerminalCommand::Data(data) => data.run(context).await?,
+            TerminalCommand::Db(db) => db.run(context).await?,
+            TerminalCommand::Exec(exec) => exec.run(context).await?,
+            TerminalCommand::Devtool(devtool) => devtool.run(context).await?,
+            TerminalCommand::Users(users) => users.run(context).await?,
             TerminalCommand::Dev(dev) => dev.run(context).await?,
             TerminalCommand::Upgrade(upgrade) => upgrade.run(context).await?,
             TerminalCommand::Update(update) => update.run(context).await?,
             // TerminalCommand::Check(check) => check.run(context).await?,
             TerminalCommand::Reload(reload) => reload.run(context).await?,
+            TerminalCommand::Restart(restart) => restart.run(context).await?,
+            TerminalCommand::Refresh(refresh) => refresh.run(context).await?,
         };
         Ok(())
     }
@@ -196,9 +231,28 @@ impl TerminalCommand {
             TerminalCommand::Build(_)
             | TerminalCommand::Start(_)
             | TerminalCommand::Reload(_)
+            | TerminalCommand::Refresh(_)
             | TerminalCommand::Dev(_)
             | TerminalCommand::Update(_) => true,
             _ => false,
         }
     }
 }
+
+#[cfg(test)]
+mod test_parse_command_line {
+    use super::*;
+
+    #[test]
+    fn quoted_arguments() {
+        let cli =
+            parse_command_line(r#"exec mongo -- mongo --eval "db.users.count()""#.into()).unwrap();
+        match cli.command {
+            TerminalCommand::Exec(exec) => {
+                assert_eq!(exec.command, vec!["mongo", "--eval", "db.users.count()"])
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert!(parse_command_line(r#"db mongo --eval "db.users.count()"#.into()).is_err());
+    }
+}
@@ -10,8 +10,8 @@ use super::{loader, CommandContext};
 
 #[derive(Args, Debug, Clone)]
 pub struct Update {
-    /// The service list to pull
-    #[clap(value_enum, default_values = &["devtool", "postgres", "mongo"])]
+    /// The service list to pull: app, devtool, postgres, mongo or a dev service name
+    #[clap(value_parser, default_values = &["devtool", "postgres", "mongo"])]
     pub services: Vec<Service>,
 }
 
@@ -22,7 +22,7 @@ impl CliCommand for Update {
     }
 }
 
-pub async fn update_loader(context: &mut CommandContext, services: &Vec<Service>) -> Result<()> {
+pub async fn update_loader(context: &mut CommandContext, services: &[Service]) -> Result<()> {
     loader(
         "Update environment images...",
         "Environment images updated",
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Create {
+    /// The email of the user
+    pub email: String,
+
+    /// Make the created user the current one
+    #[clap(long, action)]
+    pub switch: bool,
+}
+
+#[async_trait]
+impl CliCommand for Create {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let user = lenra::create_devtool_user(context, &self.email).await?;
+        println!("User {} created with the id {}", user.email, user.id);
+        if self.switch {
+            lenra::switch_devtool_user(context, &user.id.to_string()).await?;
+            println!("Current user: {}", user.email);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+//! # users
+//!
+//! The users subcommands manage the simulated users of the devtool
+
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+
+use self::{create::Create, list::List, switch::Switch, token::Token};
+
+mod create;
+mod list;
+mod switch;
+mod token;
+
+#[derive(Args, Debug, Clone)]
+pub struct Users {
+    #[clap(subcommand)]
+    command: UsersCommand,
+}
+
+/// The users subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum UsersCommand {
+    /// Create a devtool user
+    Create(Create),
+    /// List the devtool users
+    List(List),
+    /// Change the current devtool user
+    Switch(Switch),
+    /// Print an OAuth access token for a devtool user
+    Token(Token),
+}
+
+#[async_trait]
+impl CliCommand for Users {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        match &self.command {
+            UsersCommand::Create(create) => create.run(context).await,
+            UsersCommand::List(list) => list.run(context).await,
+            UsersCommand::Switch(switch) => switch.run(context).await,
+            UsersCommand::Token(token) => token.run(context).await,
+        }
+    }
+}
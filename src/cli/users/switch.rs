@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Switch {
+    /// The id or the email of the user
+    pub user: String,
+}
+
+#[async_trait]
+impl CliCommand for Switch {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let user = lenra::switch_devtool_user(context, &self.user).await?;
+        println!("Current user: {}", user.email);
+        Ok(())
+    }
+}
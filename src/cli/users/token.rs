@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+pub use clap::Args;
+
+use crate::cli::{CliCommand, CommandContext};
+use crate::errors::Result;
+use crate::lenra;
+
+#[derive(Args, Debug, Clone)]
+pub struct Token {
+    /// The id or the email of the user. Defaults to the current user
+    pub user: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand for Token {
+    async fn run(&self, context: &mut CommandContext) -> Result<()> {
+        let token = lenra::get_devtool_user_token(context, self.user.clone()).await?;
+        println!("{}", token);
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
-use std::{collections::HashMap, fmt::Debug, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs,
+    path::PathBuf,
+};
 
+use colored::{Color, Colorize};
 use dofigen_lib::{
     self, from_file_path, generate_dockerfile, generate_dockerignore, Artifact, Builder,
     Healthcheck,
@@ -9,7 +15,7 @@ use serde_yaml::Value;
 
 use crate::{
     cli::CommandContext,
-    docker_compose::generate_docker_compose,
+    docker_compose::{generate_docker_compose, AppImage, PortBinding},
     errors::{Error, Result},
 };
 
@@ -19,6 +25,8 @@ pub const LENRA_CACHE_DIRECTORY: &str = ".lenra";
 pub const DOCKERFILE_DEFAULT_PATH: [&str; 2] = [LENRA_CACHE_DIRECTORY, "Dockerfile"];
 pub const DOCKERIGNORE_DEFAULT_PATH: [&str; 2] = [LENRA_CACHE_DIRECTORY, "Dockerfile.dockerignore"];
 pub const DOCKERCOMPOSE_DEFAULT_PATH: [&str; 2] = [LENRA_CACHE_DIRECTORY, "compose.yml"];
+pub const SNAPSHOTS_DEFAULT_PATH: [&str; 2] = [LENRA_CACHE_DIRECTORY, "snapshots"];
+pub const COMPOSE_OVERRIDE_DEFAULT_PATH: &str = "compose.override.yml";
 
 pub const OF_WATCHDOG_BUILDER: &str = "of-watchdog";
 pub const OF_WATCHDOG_IMAGE: &str = "ghcr.io/openfaas/of-watchdog";
@@ -45,6 +53,73 @@ pub fn load_config_file(path: &std::path::PathBuf) -> Result<Application> {
     }
 }
 
+/// Saves the app configuration to the given file
+pub fn save_config_file(path: &std::path::PathBuf, app: &Application) -> Result<()> {
+    fs::write(path, config_to_yaml(app)?).map_err(Error::from)
+}
+
+/// Serializes the app configuration without the undefined properties
+pub fn config_to_yaml(app: &Application) -> Result<String> {
+    let value = remove_null_values(serde_yaml::to_value(app).map_err(Error::from)?);
+    serde_yaml::to_string(&value).map_err(Error::from)
+}
+
+/// Replaces the generator of a YAML config file content, keeping the rest of the file untouched
+pub fn replace_config_generator(content: &str, generator: &Generator) -> Result<String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let start = lines
+        .iter()
+        .position(|line| {
+            line.strip_prefix("generator")
+                .map(|rest| rest.trim_start().starts_with(':'))
+                .unwrap_or(false)
+        })
+        .ok_or(Error::Custom(
+            "The generator was not found at the root of the config file".into(),
+        ))?;
+    // the generator node ends at the next root line, its trailing blank lines are kept
+    let mut end = start + 1;
+    while end < lines.len() && (lines[end].trim().is_empty() || lines[end].starts_with([' ', '\t']))
+    {
+        end += 1;
+    }
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+
+    let mut node = serde_yaml::Mapping::new();
+    node.insert(
+        "generator".into(),
+        remove_null_values(serde_yaml::to_value(generator).map_err(Error::from)?),
+    );
+    let node = serde_yaml::to_string(&Value::Mapping(node)).map_err(Error::from)?;
+    let replaced = [lines[..start].concat(), node, lines[end..].concat()].concat();
+
+    let app: Application = serde_yaml::from_str(&replaced).map_err(Error::from)?;
+    if &app.generator != generator {
+        return Err(Error::Custom(
+            "The generator of the config file could not be replaced".into(),
+        ));
+    }
+    Ok(replaced)
+}
+
+fn remove_null_values(value: Value) -> Value {
+    match value {
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, remove_null_values(value)))
+                .collect(),
+        ),
+        Value::Sequence(sequence) => {
+            Value::Sequence(sequence.into_iter().map(remove_null_values).collect())
+        }
+        value => value,
+    }
+}
+
 /** The main component of the config file */
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -52,6 +127,8 @@ pub struct Application {
     pub path: Option<PathBuf>,
     pub generator: Generator,
     pub dev: Option<Dev>,
+    /// The Docker Compose file merged onto the generated one, relative to the app path
+    pub compose_override: Option<PathBuf>,
 }
 
 /** The dev specific configuration */
@@ -59,9 +136,50 @@ pub struct Application {
 pub struct Dev {
     pub app: Option<Image>,
     pub devtool: Option<DevToolConf>,
-    pub postgres: Option<Image>,
-    pub mongo: Option<Image>,
+    pub postgres: Option<DatabaseConf>,
+    pub mongo: Option<DatabaseConf>,
     pub dofigen: Option<DebugDofigen>,
+    pub mounts: Option<Vec<Mount>>,
+    pub services: Option<BTreeMap<String, DevService>>,
+    /// The host port bindings of the services published ports
+    pub ports: Option<BTreeMap<String, Vec<PortBinding>>>,
+    pub seed: Option<DevSeed>,
+}
+
+/** The scripts populating the app database when the environment starts */
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DevSeed {
+    /// The JSON or JS seed files, relative to the app path
+    pub files: Option<Vec<PathBuf>>,
+    /// The JS migration scripts, relative to the app path, applied in order after the seed files
+    pub migrations: Option<Vec<PathBuf>>,
+    /// The devtool app environment whose database is seeded
+    pub env_id: Option<u64>,
+}
+
+/** A source path mounted into the app container in dev mode */
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Mount {
+    /// The host path, relative to the app path
+    pub source: PathBuf,
+    /// The path in the app container
+    pub target: String,
+}
+
+/** A user defined service added to the dev environment */
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DevService {
+    pub image: String,
+    #[serde(alias = "env")]
+    pub envs: Option<BTreeMap<String, String>>,
+    pub command: Option<Vec<String>>,
+    /// The service ports, published when the service is exposed
+    pub ports: Option<Vec<u16>>,
+    pub healthcheck: Option<Healthcheck>,
+    /// The services started before this one
+    pub depends_on: Option<Vec<String>>,
 }
 
 /** A Docker image */
@@ -78,6 +196,18 @@ pub struct DevToolConf {
     pub image: Option<String>,
     pub tag: Option<String>,
     pub log_level: Option<String>,
+    /// The additional environment variables of the devtool service
+    #[serde(alias = "env")]
+    pub envs: Option<BTreeMap<String, String>>,
+}
+
+/** A database service, running in a container unless an external URL is set */
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct DatabaseConf {
+    pub image: Option<String>,
+    pub tag: Option<String>,
+    /// The URL of an external database used instead of the container
+    pub url: Option<String>,
 }
 
 pub trait ImageConf {
@@ -102,6 +232,16 @@ impl ImageConf for Image {
     }
 }
 
+impl ImageConf for DatabaseConf {
+    fn image(&self) -> Option<String> {
+        self.image.clone()
+    }
+
+    fn tag(&self) -> Option<String> {
+        self.tag.clone()
+    }
+}
+
 impl ImageConf for DevToolConf {
     fn image(&self) -> Option<String> {
         self.image.clone()
@@ -112,9 +252,25 @@ impl ImageConf for DevToolConf {
     }
 }
 
+/** The Dofigen overrides applied to the dev builds */
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct DebugDofigen {
+    /// Replaces the base image
+    #[serde(alias = "from")]
+    pub image: Option<String>,
+    /// Replaces the app process command
     pub cmd: Option<Vec<String>>,
+    #[serde(alias = "env")]
+    pub envs: Option<HashMap<String, String>>,
+    pub builders: Option<Vec<Builder>>,
+    pub artifacts: Option<Vec<Artifact>>,
+    #[serde(alias = "add")]
+    pub adds: Option<Vec<String>>,
+    #[serde(alias = "run")]
+    pub script: Option<Vec<String>>,
+    #[serde(alias = "cache")]
+    pub caches: Option<Vec<String>>,
+    /// Additional exposed ports
     pub ports: Option<Vec<u16>>,
 }
 
@@ -127,6 +283,7 @@ pub enum Generator {
     DofigenError { dofigen: Value },
     Dockerfile(Dockerfile),
     Docker(Docker),
+    Image(PrebuiltImage),
     Unknow,
 }
 
@@ -147,15 +304,40 @@ pub struct DofigenFile {
 pub struct Docker {
     pub docker: String,
     pub ignore: Option<String>,
+    pub watchdog: Option<Watchdog>,
 }
 
 /** The Docker configuration file */
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct Dockerfile {
+    /// Rejects the Dockerfile contents so that they are deserialized as the Docker generator
+    #[serde(deserialize_with = "deserialize_dockerfile_path")]
     pub docker: std::path::PathBuf,
+    pub watchdog: Option<Watchdog>,
+}
+
+/** A prebuilt app image pulled instead of being built */
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct PrebuiltImage {
+    pub image: String,
+}
+
+/** The OpenFaaS of-watchdog overlay applied to a user Dockerfile */
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Watchdog {
+    /// The app process command started by the watchdog
+    pub cmd: Vec<String>,
+    /// The port the app listens to in http mode
+    pub port: Option<u16>,
+    pub healthcheck: Option<Healthcheck>,
 }
 
 impl Application {
+    /// Checks if the app image is pulled from a registry instead of being built
+    pub fn is_prebuilt(&self) -> bool {
+        matches!(self.generator, Generator::Image(_))
+    }
+
     /// Generates all the files needed to build and run the application
     pub async fn generate_files(&self, context: &mut CommandContext, debug: bool) -> Result<()> {
         self.generate_docker_files(context, debug)?;
@@ -182,10 +364,35 @@ impl Application {
             Generator::DofigenError { dofigen: _ } => Err(Error::Custom(
                 "Your Dofigen configuration is not correct".into(),
             )),
-            Generator::Dockerfile(_dockerfile) => Ok(()),
+            Generator::Dockerfile(dockerfile) => {
+                let dockerfile_path = context.resolve_path(&dockerfile.docker);
+                let content = fs::read_to_string(&dockerfile_path)
+                    .map_err(|err| Error::OpenFile(err, dockerfile_path.clone()))?;
+                if let Some(watchdog) = &dockerfile.watchdog {
+                    // keep the Dockerfile specific ignore file next to the generated Dockerfile
+                    let mut dockerignore_path = dockerfile_path.into_os_string();
+                    dockerignore_path.push(".dockerignore");
+                    let dockerignore = fs::read_to_string(dockerignore_path).ok();
+                    self.save_docker_content(
+                        context,
+                        dockerfile_of_overlay(content, watchdog)?,
+                        dockerignore,
+                    )
+                } else {
+                    lint_dockerfile(&content);
+                    Ok(())
+                }
+            }
             Generator::Docker(docker) => {
-                self.save_docker_content(context, docker.docker.clone(), docker.ignore.clone())
+                let dockerfile = if let Some(watchdog) = &docker.watchdog {
+                    dockerfile_of_overlay(docker.docker.clone(), watchdog)?
+                } else {
+                    lint_dockerfile(&docker.docker);
+                    docker.docker.clone()
+                };
+                self.save_docker_content(context, dockerfile, docker.ignore.clone())
             }
+            Generator::Image(_) => Ok(()),
             Generator::Unknow => Err(Error::Custom("Not managed generator".into())),
         }
     }
@@ -200,13 +407,33 @@ impl Application {
         fs::create_dir_all(context.resolve_path(&PathBuf::from(LENRA_CACHE_DIRECTORY)))
             .map_err(Error::from)?;
 
-        let dockerfile: PathBuf = if let Generator::Dockerfile(file_conf) = &self.generator {
-            context.resolve_path(&file_conf.docker.clone())
-        } else {
-            context.resolve_path(&DOCKERFILE_DEFAULT_PATH.iter().collect())
+        let app_image = match &self.generator {
+            Generator::Dockerfile(Dockerfile {
+                docker,
+                watchdog: None,
+            }) => AppImage::Dockerfile(context.resolve_path(&docker.clone())),
+            Generator::Image(prebuilt) => AppImage::Prebuilt(prebuilt.image.clone()),
+            _ => AppImage::Dockerfile(
+                context.resolve_path(&DOCKERFILE_DEFAULT_PATH.iter().collect()),
+            ),
         };
 
-        generate_docker_compose(context, dockerfile, &self.dev, debug)
+        let compose_override = match &self.compose_override {
+            Some(path) => {
+                let path = context.resolve_path(path);
+                if !path.exists() {
+                    return Err(Error::Custom(format!(
+                        "The compose override file {:?} does not exist",
+                        path
+                    )));
+                }
+                Some(path)
+            }
+            None => Some(context.resolve_path(&PathBuf::from(COMPOSE_OVERRIDE_DEFAULT_PATH)))
+                .filter(|path| path.exists()),
+        };
+
+        generate_docker_compose(context, app_image, &self.dev, compose_override, debug)
             .await
             .map_err(Error::from)?;
         Ok(())
@@ -219,6 +446,16 @@ impl Application {
         image: dofigen_lib::Image,
         debug: bool,
     ) -> Result<()> {
+        let (dockerfile, dockerignore) = self.dofigen_docker_content(image, debug)?;
+        self.save_docker_content(context, dockerfile, Some(dockerignore))
+    }
+
+    /// Generates the Dockerfile and .dockerignore contents from a Dofigen structure
+    fn dofigen_docker_content(
+        &self,
+        image: dofigen_lib::Image,
+        debug: bool,
+    ) -> Result<(String, String)> {
         // Generate the Dofigen config with OpenFaaS overlay to handle the of-watchdog
         let overlay = self.dofigen_of_overlay(image)?;
 
@@ -230,9 +467,26 @@ impl Application {
         };
 
         // generate the Dockerfile and .dockerignore files with Dofigen
-        let dockerfile = generate_dockerfile(&overlay);
-        let dockerignore = generate_dockerignore(&overlay);
-        self.save_docker_content(context, dockerfile, Some(dockerignore))
+        Ok((
+            generate_dockerfile(&overlay),
+            generate_dockerignore(&overlay),
+        ))
+    }
+
+    /// Generates the release Dockerfile and .dockerignore contents of a Dofigen generator
+    pub fn eject_dofigen(&self, context: &CommandContext) -> Result<(String, String)> {
+        let image = match &self.generator {
+            Generator::Dofigen(dofigen) => dofigen.dofigen.clone(),
+            Generator::DofigenFile(dofigen_file) => {
+                from_file_path(&context.resolve_path(&dofigen_file.dofigen)).map_err(Error::from)?
+            }
+            _ => {
+                return Err(Error::Custom(
+                    "Only the Dofigen generators can be ejected".into(),
+                ))
+            }
+        };
+        self.dofigen_docker_content(image, false)
     }
 
     fn dofigen_debug_overlay(&self, image: dofigen_lib::Image) -> Result<dofigen_lib::Image> {
@@ -240,21 +494,23 @@ impl Application {
         let mut debug_overlay = image;
         if let Some(dev) = &self.dev {
             if let Some(dofigen) = &dev.dofigen {
+                if let Some(image) = &dofigen.image {
+                    debug_overlay.image = image.clone();
+                }
+                let mut envs = debug_overlay.envs.unwrap_or_default();
+                if let Some(dev_envs) = &dofigen.envs {
+                    envs.extend(dev_envs.clone());
+                }
                 if let Some(cmd) = &dofigen.cmd {
-                    let mut envs = debug_overlay.envs.unwrap();
                     envs.insert("fprocess".to_string(), cmd.join(" "));
-                    debug_overlay.envs = Some(envs);
-                }
-                if let Some(ports) = &dofigen.ports {
-                    debug_overlay.ports = Some(
-                        debug_overlay
-                            .ports
-                            .unwrap()
-                            .into_iter()
-                            .chain(ports.into_iter().map(|&value| value))
-                            .collect(),
-                    )
                 }
+                debug_overlay.envs = Some(envs);
+                debug_overlay.builders = merge_vec(debug_overlay.builders, &dofigen.builders);
+                debug_overlay.artifacts = merge_vec(debug_overlay.artifacts, &dofigen.artifacts);
+                debug_overlay.adds = merge_vec(debug_overlay.adds, &dofigen.adds);
+                debug_overlay.script = merge_vec(debug_overlay.script, &dofigen.script);
+                debug_overlay.caches = merge_vec(debug_overlay.caches, &dofigen.caches);
+                debug_overlay.ports = merge_vec(debug_overlay.ports, &dofigen.ports);
             }
         }
         Ok(debug_overlay)
@@ -287,57 +543,24 @@ impl Application {
             destination: "/fwatchdog".to_string(),
         });
 
-        let mut envs = if let Some(envs) = image.envs {
+        let envs = if let Some(envs) = image.envs {
             envs
         } else {
             HashMap::new()
         };
 
-        let mut healthcheck = None;
         // http mode (not if empty)
-        if let Some(ports) = image.ports {
+        let port = if let Some(ports) = image.ports {
             if ports.len() > 1 {
                 return Err(Error::Custom(
                     "More than one port has been defined in the Dofigen descriptor".into(),
                 ));
             }
-            if ports.len() == 1 {
-                envs.insert("mode".to_string(), "http".to_string());
-                envs.insert(
-                    "upstream_url".to_string(),
-                    format!("http://127.0.0.1:{}", ports[0]),
-                );
-                envs.insert("suppress_lock".to_string(), "true".to_string());
-                if !envs.contains_key("exec_timeout") {
-                    envs.insert("exec_timeout".to_string(), "3600".to_string());
-                }
-                if !envs.contains_key("read_timeout") {
-                    envs.insert("read_timeout".to_string(), "3600".to_string());
-                }
-                if !envs.contains_key("write_timeout") {
-                    envs.insert("write_timeout".to_string(), "3600".to_string());
-                }
-                // handle healthcheck
-                let default_healthcheck = Healthcheck {
-                    cmd: "curl --fail http://localhost:8080/_/health".into(),
-                    start: Some("3s".into()),
-                    interval: Some("3s".into()),
-                    timeout: Some("1s".into()),
-                    retries: Some(10),
-                };
-                healthcheck = Some(if let Some(healthcheck) = image.healthcheck {
-                    Healthcheck {
-                        cmd: healthcheck.cmd,
-                        start: healthcheck.start.or(default_healthcheck.start),
-                        interval: healthcheck.interval.or(default_healthcheck.interval),
-                        timeout: healthcheck.timeout.or(default_healthcheck.timeout),
-                        retries: healthcheck.retries.or(default_healthcheck.retries),
-                    }
-                } else {
-                    default_healthcheck
-                });
-            }
+            ports.first().copied()
+        } else {
+            None
         };
+        let (mut envs, healthcheck) = of_watchdog_settings(envs, port, image.healthcheck);
 
         // prevent custom entrypoint
         if image.entrypoint.is_some() {
@@ -396,6 +619,193 @@ impl Application {
     }
 }
 
+/// Appends the additional elements to the base list
+fn merge_vec<T: Clone>(base: Option<Vec<T>>, additional: &Option<Vec<T>>) -> Option<Vec<T>> {
+    match (base, additional) {
+        (Some(mut base), Some(additional)) => {
+            base.extend(additional.iter().cloned());
+            Some(base)
+        }
+        (None, Some(additional)) => Some(additional.clone()),
+        (base, None) => base,
+    }
+}
+
+/// Computes the of-watchdog environment variables and healthcheck.
+/// The http mode is only enabled when the app listens to a port.
+fn of_watchdog_settings(
+    mut envs: HashMap<String, String>,
+    port: Option<u16>,
+    healthcheck: Option<Healthcheck>,
+) -> (HashMap<String, String>, Option<Healthcheck>) {
+    let port = match port {
+        Some(port) => port,
+        None => return (envs, None),
+    };
+    envs.insert("mode".to_string(), "http".to_string());
+    envs.insert(
+        "upstream_url".to_string(),
+        format!("http://127.0.0.1:{}", port),
+    );
+    envs.insert("suppress_lock".to_string(), "true".to_string());
+    if !envs.contains_key("exec_timeout") {
+        envs.insert("exec_timeout".to_string(), "3600".to_string());
+    }
+    if !envs.contains_key("read_timeout") {
+        envs.insert("read_timeout".to_string(), "3600".to_string());
+    }
+    if !envs.contains_key("write_timeout") {
+        envs.insert("write_timeout".to_string(), "3600".to_string());
+    }
+    // handle healthcheck
+    let default_healthcheck = Healthcheck {
+        cmd: "curl --fail http://localhost:8080/_/health".into(),
+        start: Some("3s".into()),
+        interval: Some("3s".into()),
+        timeout: Some("1s".into()),
+        retries: Some(10),
+    };
+    let healthcheck = if let Some(healthcheck) = healthcheck {
+        Healthcheck {
+            cmd: healthcheck.cmd,
+            start: healthcheck.start.or(default_healthcheck.start),
+            interval: healthcheck.interval.or(default_healthcheck.interval),
+            timeout: healthcheck.timeout.or(default_healthcheck.timeout),
+            retries: healthcheck.retries.or(default_healthcheck.retries),
+        }
+    } else {
+        default_healthcheck
+    };
+    (envs, Some(healthcheck))
+}
+
+/// Wraps a user Dockerfile to run the app behind the OpenFaaS of-watchdog.
+/// The watchdog binary is copied from its image in the last stage of the Dockerfile.
+fn dockerfile_of_overlay(dockerfile: String, watchdog: &Watchdog) -> Result<String> {
+    if watchdog.cmd.is_empty() {
+        return Err(Error::Custom(
+            "The watchdog cmd property is not defined".into(),
+        ));
+    }
+    let (mut envs, healthcheck) =
+        of_watchdog_settings(HashMap::new(), watchdog.port, watchdog.healthcheck.clone());
+    envs.insert("fprocess".to_string(), watchdog.cmd.join(" "));
+
+    let (dockerfile, final_stage) = name_final_stage(&dockerfile)?;
+    let mut buffer = dockerfile.trim_end().to_string();
+    buffer.push_str("\n\n# of-watchdog overlay generated by the Lenra CLI\n");
+    buffer.push_str(format!("FROM {} AS {}\n", final_stage, WATCHDOG_STAGE).as_str());
+    buffer.push_str(
+        format!(
+            "COPY --from={}:{} /fwatchdog /fwatchdog\n",
+            OF_WATCHDOG_IMAGE, OF_WATCHDOG_VERSION
+        )
+        .as_str(),
+    );
+    let mut keys: Vec<&String> = envs.keys().collect();
+    keys.sort();
+    for key in keys {
+        buffer.push_str(
+            format!(
+                "ENV {}={}\n",
+                key,
+                serde_json::to_string(&envs[key]).map_err(|err| Error::Custom(err.to_string()))?
+            )
+            .as_str(),
+        );
+    }
+    buffer.push_str("EXPOSE 8080\n");
+    if let Some(healthcheck) = healthcheck {
+        buffer.push_str("HEALTHCHECK ");
+        if let Some(interval) = &healthcheck.interval {
+            buffer.push_str(format!("--interval={} ", interval).as_str());
+        }
+        if let Some(timeout) = &healthcheck.timeout {
+            buffer.push_str(format!("--timeout={} ", timeout).as_str());
+        }
+        if let Some(start) = &healthcheck.start {
+            buffer.push_str(format!("--start-period={} ", start).as_str());
+        }
+        if let Some(retries) = &healthcheck.retries {
+            buffer.push_str(format!("--retries={} ", retries).as_str());
+        }
+        buffer.push_str(format!("CMD {}\n", healthcheck.cmd).as_str());
+    }
+    buffer.push_str("ENTRYPOINT []\n");
+    buffer.push_str("CMD [\"/fwatchdog\"]\n");
+    Ok(buffer)
+}
+
+/// The name given to the final stage of a Dockerfile when it has none
+const APP_STAGE: &str = "lenra-app";
+/// The name of the of-watchdog overlay stage
+const WATCHDOG_STAGE: &str = "lenra-watchdog";
+
+/// Returns the Dockerfile with a named final stage and the name of this stage
+fn name_final_stage(dockerfile: &str) -> Result<(String, String)> {
+    let mut lines: Vec<String> = dockerfile.lines().map(String::from).collect();
+    let from_index = lines
+        .iter()
+        .rposition(|line| {
+            line.split_whitespace()
+                .next()
+                .map(|instruction| instruction.eq_ignore_ascii_case("FROM"))
+                .unwrap_or(false)
+        })
+        .ok_or(Error::Custom(
+            "The Dockerfile has no FROM instruction".into(),
+        ))?;
+    let words: Vec<&str> = lines[from_index].split_whitespace().collect();
+    let name = match words[..] {
+        [.., keyword, name] if words.len() > 3 && keyword.eq_ignore_ascii_case("AS") => {
+            name.to_string()
+        }
+        _ => {
+            lines[from_index] = format!("{} AS {}", lines[from_index].trim_end(), APP_STAGE);
+            APP_STAGE.to_string()
+        }
+    };
+    Ok((lines.join("\n"), name))
+}
+
+/// Deserializes a Dockerfile path, failing for a Dockerfile content
+fn deserialize_dockerfile_path<'de, D>(deserializer: D) -> std::result::Result<PathBuf, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let path = String::deserialize(deserializer)?;
+    let is_content = path.contains('\n')
+        || path
+            .split_whitespace()
+            .next()
+            .map(|word| word.eq_ignore_ascii_case("FROM"))
+            .unwrap_or(false);
+    if is_content {
+        Err(serde::de::Error::custom("not a Dockerfile path"))
+    } else {
+        Ok(PathBuf::from(path))
+    }
+}
+
+/// Checks that the Dockerfile starts the of-watchdog
+fn has_watchdog_entrypoint(dockerfile: &str) -> bool {
+    dockerfile.lines().any(|line| {
+        let line = line.trim_start().to_uppercase();
+        (line.starts_with("ENTRYPOINT") || line.starts_with("CMD")) && line.contains("FWATCHDOG")
+    })
+}
+
+/// Warns the user when a plain Dockerfile does not start the of-watchdog
+fn lint_dockerfile(dockerfile: &str) {
+    if !has_watchdog_entrypoint(dockerfile) {
+        eprintln!(
+            "{}",
+            "Your Dockerfile does not seem to start the of-watchdog. Start it yourself or define the generator watchdog property to add it automatically."
+                .color(Color::Yellow)
+        );
+    }
+}
+
 impl Default for Generator {
     fn default() -> Self {
         Generator::Unknow
@@ -462,3 +872,232 @@ mod dofigen_of_overlay_tests {
         config.dofigen_of_overlay(image).unwrap();
     }
 }
+
+#[cfg(test)]
+mod dofigen_debug_overlay_tests {
+    use super::*;
+
+    #[test]
+    fn without_dev_config() {
+        let image = dofigen_lib::Image {
+            image: "my-dockerimage".into(),
+            ..Default::default()
+        };
+        let config = Application::default();
+        assert_eq!(config.dofigen_debug_overlay(image.clone()).unwrap(), image);
+    }
+
+    #[test]
+    fn cmd_without_envs() {
+        let image = dofigen_lib::Image {
+            image: "my-dockerimage".into(),
+            ..Default::default()
+        };
+        let config = Application {
+            dev: Some(Dev {
+                dofigen: Some(DebugDofigen {
+                    cmd: Some(vec!["/app/my-app".into()]),
+                    ports: Some(vec![9229]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let overlayed_image = config.dofigen_debug_overlay(image).unwrap();
+        assert_eq!(
+            overlayed_image.envs,
+            Some([("fprocess".to_string(), "/app/my-app".to_string())].into())
+        );
+        assert_eq!(overlayed_image.ports, Some(vec![9229]));
+    }
+
+    #[test]
+    fn merged_overrides() {
+        let image = dofigen_lib::Image {
+            image: "my-dockerimage".into(),
+            envs: Some([("fprocess".to_string(), "/app/my-app".to_string())].into()),
+            adds: Some(vec!["src".into()]),
+            ports: Some(vec![8080]),
+            ..Default::default()
+        };
+        let config = Application {
+            dev: Some(Dev {
+                dofigen: Some(DebugDofigen {
+                    image: Some("my-debug-image".into()),
+                    cmd: Some(vec!["/app/my-app".into(), "--inspect".into()]),
+                    envs: Some([("DEBUG".to_string(), "true".to_string())].into()),
+                    builders: Some(vec![Builder {
+                        name: Some("debugger".into()),
+                        image: "my-debugger".into(),
+                        ..Default::default()
+                    }]),
+                    adds: Some(vec!["test".into()]),
+                    script: Some(vec!["npm install --dev".into()]),
+                    ports: Some(vec![9229]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let overlayed_image = config.dofigen_debug_overlay(image).unwrap();
+        assert_eq!(
+            overlayed_image,
+            dofigen_lib::Image {
+                image: "my-debug-image".into(),
+                envs: Some(
+                    [
+                        ("fprocess".to_string(), "/app/my-app --inspect".to_string()),
+                        ("DEBUG".to_string(), "true".to_string()),
+                    ]
+                    .into()
+                ),
+                builders: Some(vec![Builder {
+                    name: Some("debugger".into()),
+                    image: "my-debugger".into(),
+                    ..Default::default()
+                }]),
+                adds: Some(vec!["src".into(), "test".into()]),
+                script: Some(vec!["npm install --dev".into()]),
+                ports: Some(vec![8080, 9229]),
+                ..Default::default()
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod dockerfile_of_overlay_tests {
+    use super::*;
+
+    #[test]
+    fn simple_dockerfile() {
+        let dockerfile = "FROM node:18\nCOPY . .\n";
+        let watchdog = Watchdog {
+            cmd: vec!["node".into(), "index.js".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            dockerfile_of_overlay(dockerfile.into(), &watchdog).unwrap(),
+            format!(
+                "FROM node:18 AS lenra-app\nCOPY . .\n\n# of-watchdog overlay generated by the Lenra CLI\nFROM lenra-app AS lenra-watchdog\nCOPY --from=ghcr.io/openfaas/of-watchdog:{} /fwatchdog /fwatchdog\nENV fprocess=\"node index.js\"\nEXPOSE 8080\nENTRYPOINT []\nCMD [\"/fwatchdog\"]\n",
+                OF_WATCHDOG_VERSION
+            )
+        );
+    }
+
+    #[test]
+    fn http_mode() {
+        let watchdog = Watchdog {
+            cmd: vec!["node".into(), "index.js".into()],
+            port: Some(3000),
+            ..Default::default()
+        };
+        let overlayed = dockerfile_of_overlay("FROM node:18".into(), &watchdog).unwrap();
+        assert!(overlayed.contains("ENV mode=\"http\"\n"));
+        assert!(overlayed.contains("ENV upstream_url=\"http://127.0.0.1:3000\"\n"));
+        assert!(overlayed.contains(
+            "HEALTHCHECK --interval=3s --timeout=1s --start-period=3s --retries=10 CMD curl --fail http://localhost:8080/_/health\n"
+        ));
+        assert!(has_watchdog_entrypoint(&overlayed));
+    }
+
+    #[test]
+    fn named_final_stage() {
+        let dockerfile = "FROM golang AS build\nRUN go build\nfrom --platform=linux/amd64 alpine as runtime\nCOPY --from=build /app /app";
+        let (named, stage) = name_final_stage(dockerfile).unwrap();
+        assert_eq!(named, dockerfile);
+        assert_eq!(stage, "runtime");
+
+        let (named, stage) = name_final_stage("FROM golang AS build\nFROM alpine\n").unwrap();
+        assert_eq!(named, "FROM golang AS build\nFROM alpine AS lenra-app");
+        assert_eq!(stage, APP_STAGE);
+
+        assert!(name_final_stage("# no stage").is_err());
+    }
+
+    #[test]
+    fn docker_generator() {
+        let generator: Generator = serde_yaml::from_str(
+            "docker: |\n  FROM node:18\n  COPY . .\nwatchdog:\n  cmd: [node, index.js]\n",
+        )
+        .unwrap();
+        let docker = match generator {
+            Generator::Docker(docker) => docker,
+            generator => panic!("Unexpected generator {:?}", generator),
+        };
+        let overlayed =
+            dockerfile_of_overlay(docker.docker, docker.watchdog.as_ref().unwrap()).unwrap();
+        assert!(overlayed.contains("FROM node:18 AS lenra-app\n"));
+        assert!(overlayed.contains("FROM lenra-app AS lenra-watchdog\n"));
+        assert!(has_watchdog_entrypoint(&overlayed));
+
+        let generator: Generator = serde_yaml::from_str("docker: Dockerfile\n").unwrap();
+        assert_eq!(
+            generator,
+            Generator::Dockerfile(Dockerfile {
+                docker: PathBuf::from("Dockerfile"),
+                watchdog: None
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_cmd() {
+        dockerfile_of_overlay("FROM node:18".into(), &Watchdog::default()).unwrap();
+    }
+
+    #[test]
+    fn lint_entrypoint() {
+        assert!(has_watchdog_entrypoint(
+            "FROM node:18\nCMD [\"/fwatchdog\"]"
+        ));
+        assert!(has_watchdog_entrypoint(
+            "FROM node:18\nentrypoint /usr/bin/fwatchdog"
+        ));
+        assert!(!has_watchdog_entrypoint(
+            "FROM node:18\nCMD [\"node\", \"index.js\"]"
+        ));
+    }
+}
+
+#[cfg(test)]
+mod replace_config_generator_tests {
+    use super::*;
+
+    fn docker_generator() -> Generator {
+        Generator::Dockerfile(Dockerfile {
+            docker: PathBuf::from("Dockerfile"),
+            watchdog: None,
+        })
+    }
+
+    #[test]
+    fn keeps_comments_and_order() {
+        let content = "# My app\ncomponentsApi: \"1.0\"\n\n# How to build it\ngenerator:\n  dofigen:\n    from: node:18\n    # the start command\n    cmd: [node, index.js]\n\n# Dev settings\ndev:\n  dofigen:\n    cmd: [node, --inspect, index.js]\n";
+        assert_eq!(
+            replace_config_generator(content, &docker_generator()).unwrap(),
+            "# My app\ncomponentsApi: \"1.0\"\n\n# How to build it\ngenerator:\n  docker: Dockerfile\n\n# Dev settings\ndev:\n  dofigen:\n    cmd: [node, --inspect, index.js]\n"
+        );
+    }
+
+    #[test]
+    fn last_node() {
+        let content = "componentsApi: \"1.0\"\ngenerator:\n  dofigen:\n    from: node:18\n    cmd: [node, index.js]";
+        assert_eq!(
+            replace_config_generator(content, &docker_generator()).unwrap(),
+            "componentsApi: \"1.0\"\ngenerator:\n  docker: Dockerfile\n"
+        );
+    }
+
+    #[test]
+    fn missing_generator() {
+        assert!(replace_config_generator(
+            "{\"componentsApi\": \"1.0\", \"generator\": {\"dofigen\": {\"from\": \"node:18\"}}}",
+            &docker_generator()
+        )
+        .is_err());
+    }
+}
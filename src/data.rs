@@ -0,0 +1,491 @@
+//! Dumps and restores the databases data of the app environment as a single archive
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use chrono::{SecondsFormat, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::CommandContext,
+    devtool::{app_database, DEFAULT_ENV_ID},
+    docker_compose::{
+        execute_compose_service_command, execute_compose_service_command_with_input, Service,
+        ServiceImages, DEVTOOL_DATABASE,
+    },
+    errors::{Error, Result},
+};
+
+const METADATA_FILE: &str = "metadata.yml";
+const MONGO_DUMP_FILE: &str = "mongo.archive";
+const POSTGRES_DUMP_FILE: &str = "postgres.sql";
+const SNAPSHOT_EXTENSION: &str = ".tar.gz";
+
+lazy_static! {
+    static ref SNAPSHOT_NAME_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9][a-zA-Z0-9_.-]*$").unwrap();
+}
+
+/// The description of a data archive
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DataMetadata {
+    pub cli_version: String,
+    pub created_at: String,
+    /// The environment images by service name
+    pub images: BTreeMap<String, String>,
+    /// The devtool app environment whose data is archived
+    #[serde(default = "default_env_id")]
+    pub env_id: u64,
+}
+
+fn default_env_id() -> u64 {
+    DEFAULT_ENV_ID
+}
+
+impl DataMetadata {
+    pub fn new(images: &ServiceImages, env_id: u64) -> Self {
+        DataMetadata {
+            cli_version: env!("CARGO_PKG_VERSION").into(),
+            created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            images: images_map(images),
+            env_id,
+        }
+    }
+
+    /// Lists the services whose image is different from the given ones
+    pub fn changed_images(&self, images: &ServiceImages) -> Vec<(String, String, String)> {
+        images_map(images)
+            .into_iter()
+            .filter_map(|(service, image)| match self.images.get(&service) {
+                Some(archived) if archived != &image => Some((service, archived.clone(), image)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn images_map(images: &ServiceImages) -> BTreeMap<String, String> {
+    [
+        (Service::App, &images.app),
+        (Service::Devtool, &images.devtool),
+        (Service::Postgres, &images.postgres),
+        (Service::Mongo, &images.mongo),
+    ]
+    .into_iter()
+    .map(|(service, image)| (service.to_string(), image.clone()))
+    .collect()
+}
+
+/// The databases data of the app environment
+#[derive(Debug, PartialEq, Clone)]
+pub struct DataArchive {
+    pub metadata: DataMetadata,
+    /// The mongodump archive of the app data
+    pub mongo: Vec<u8>,
+    /// The SQL dump of the devtool database
+    pub postgres: Option<Vec<u8>>,
+}
+
+impl DataArchive {
+    /// Dumps the databases of the running environment
+    pub async fn dump(
+        context: &mut CommandContext,
+        metadata: DataMetadata,
+        with_devtool: bool,
+    ) -> Result<Self> {
+        log::debug!("Dump the Mongo data");
+        let database = app_database(metadata.env_id);
+        let mongo = execute_compose_service_command_with_input(
+            context,
+            Service::Mongo,
+            &[
+                "mongodump",
+                "--quiet",
+                "--archive",
+                "--gzip",
+                "--db",
+                &database,
+            ],
+            None,
+        )
+        .await?;
+        let postgres = if with_devtool {
+            log::debug!("Dump the devtool Postgres data");
+            Some(
+                execute_compose_service_command_with_input(
+                    context,
+                    Service::Postgres,
+                    &[
+                        "pg_dump",
+                        "-U",
+                        "postgres",
+                        "--clean",
+                        "--if-exists",
+                        DEVTOOL_DATABASE,
+                    ],
+                    None,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        Ok(DataArchive {
+            metadata,
+            mongo,
+            postgres,
+        })
+    }
+
+    /// Restores the databases of the running environment, replacing their current data.
+    /// The Mongo data is restored to the database of the given app environment,
+    /// dropped first so that the collections missing from the archive are removed too.
+    pub async fn restore(
+        &self,
+        context: &mut CommandContext,
+        env_id: u64,
+        mongo_shell: &str,
+    ) -> Result<()> {
+        log::debug!("Drop the Mongo app database");
+        let command = drop_database_command(mongo_shell, &app_database(env_id));
+        let command: Vec<&str> = command.iter().map(String::as_str).collect();
+        execute_compose_service_command(context, Service::Mongo, &command).await?;
+        log::debug!("Restore the Mongo data");
+        let command = mongorestore_command(self.metadata.env_id, env_id);
+        let command: Vec<&str> = command.iter().map(String::as_str).collect();
+        execute_compose_service_command_with_input(
+            context,
+            Service::Mongo,
+            &command,
+            Some(self.mongo.clone()),
+        )
+        .await?;
+        if let Some(postgres) = &self.postgres {
+            log::debug!("Restore the devtool Postgres data");
+            execute_compose_service_command_with_input(
+                context,
+                Service::Postgres,
+                // a failing statement aborts the whole import instead of half applying it
+                &[
+                    "psql",
+                    "-q",
+                    "-v",
+                    "ON_ERROR_STOP=1",
+                    "--single-transaction",
+                    "-U",
+                    "postgres",
+                    "-d",
+                    DEVTOOL_DATABASE,
+                ],
+                Some(postgres.clone()),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Writes the archive as a gzipped tar file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::File::create(path).map_err(|err| Error::OpenFile(err, path.into()))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let metadata = serde_yaml::to_string(&self.metadata).map_err(Error::from)?;
+        let mut entries = vec![
+            (METADATA_FILE, metadata.as_bytes()),
+            (MONGO_DUMP_FILE, &self.mongo[..]),
+        ];
+        if let Some(postgres) = &self.postgres {
+            entries.push((POSTGRES_DUMP_FILE, &postgres[..]));
+        }
+        for (name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(Utc::now().timestamp() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, name, content)?;
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    /// Reads an archive written by the save method
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut archive = open_archive(path)?;
+        let mut files: BTreeMap<PathBuf, Vec<u8>> = BTreeMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_path_buf();
+            let mut content = vec![];
+            entry.read_to_end(&mut content)?;
+            files.insert(name, content);
+        }
+        let invalid = |missing: &str| {
+            Error::Custom(format!(
+                "The data archive {:?} is not valid: {} is missing",
+                path, missing
+            ))
+        };
+        let metadata = files
+            .remove(Path::new(METADATA_FILE))
+            .ok_or_else(|| invalid(METADATA_FILE))?;
+        Ok(DataArchive {
+            metadata: serde_yaml::from_slice(&metadata).map_err(Error::from)?,
+            mongo: files
+                .remove(Path::new(MONGO_DUMP_FILE))
+                .ok_or_else(|| invalid(MONGO_DUMP_FILE))?,
+            postgres: files.remove(Path::new(POSTGRES_DUMP_FILE)),
+        })
+    }
+
+    /// Reads only the metadata of an archive written by the save method
+    pub fn load_metadata(path: &Path) -> Result<DataMetadata> {
+        // the metadata being the first entry, the rest of the archive is not decompressed
+        for entry in open_archive(path)?.entries()? {
+            let mut entry = entry?;
+            if entry.path()? == Path::new(METADATA_FILE) {
+                let mut content = vec![];
+                entry.read_to_end(&mut content)?;
+                return serde_yaml::from_slice(&content).map_err(Error::from);
+            }
+        }
+        Err(Error::Custom(format!(
+            "The data archive {:?} is not valid: {} is missing",
+            path, METADATA_FILE
+        )))
+    }
+}
+
+fn open_archive(path: &Path) -> Result<tar::Archive<GzDecoder<fs::File>>> {
+    let file = fs::File::open(path).map_err(|err| Error::OpenFile(err, path.into()))?;
+    Ok(tar::Archive::new(GzDecoder::new(file)))
+}
+
+/// A data archive saved in the snapshots directory of the app
+#[derive(Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    /// The archive metadata, missing if it could not be read
+    pub metadata: Option<DataMetadata>,
+}
+
+/// Returns the path of the snapshot with the given name in the snapshots directory
+pub fn snapshot_path(snapshots_dir: &Path, name: &str) -> Result<PathBuf> {
+    if !SNAPSHOT_NAME_REGEX.is_match(name) {
+        return Err(Error::Custom(format!(
+            "Invalid snapshot name '{}': it must start with a letter or a digit and only contain letters, digits, '_', '.' or '-'",
+            name
+        )));
+    }
+    Ok(snapshots_dir.join(format!("{}{}", name, SNAPSHOT_EXTENSION)))
+}
+
+/// Lists the snapshots of the snapshots directory sorted by name
+pub fn list_snapshots(snapshots_dir: &Path) -> Result<Vec<Snapshot>> {
+    if !snapshots_dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut snapshots = vec![];
+    for entry in fs::read_dir(snapshots_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let name = match file_name.strip_suffix(SNAPSHOT_EXTENSION) {
+            Some(name) if SNAPSHOT_NAME_REGEX.is_match(name) => name.to_string(),
+            _ => continue,
+        };
+        let path = entry.path();
+        let metadata = DataArchive::load_metadata(&path)
+            .map_err(|error| log::warn!("Could not read the {} snapshot: {}", name, error))
+            .ok();
+        snapshots.push(Snapshot {
+            name,
+            size: entry.metadata()?.len(),
+            path,
+            metadata,
+        });
+    }
+    snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(snapshots)
+}
+
+/// The command dropping a Mongo database with the given Mongo shell
+fn drop_database_command(mongo_shell: &str, database: &str) -> Vec<String> {
+    [
+        mongo_shell,
+        "--quiet",
+        database,
+        "--eval",
+        "db.dropDatabase()",
+    ]
+    .map(String::from)
+    .into()
+}
+
+/// The command restoring the app database of an archive to the app database of an environment.
+/// Only the app database is restored, the system ones are left untouched.
+fn mongorestore_command(from_env_id: u64, to_env_id: u64) -> Vec<String> {
+    let from = format!("{}.*", app_database(from_env_id));
+    let to = format!("{}.*", app_database(to_env_id));
+    let mut command: Vec<String> = [
+        "mongorestore",
+        "--quiet",
+        "--archive",
+        "--gzip",
+        "--drop",
+        "--nsInclude",
+    ]
+    .map(String::from)
+    .into();
+    command.push(from.clone());
+    if from != to {
+        command.extend(["--nsFrom".into(), from, "--nsTo".into(), to]);
+    }
+    command
+}
+
+#[cfg(test)]
+mod test_data_archive {
+    use std::env;
+
+    use super::*;
+
+    fn images(devtool_tag: &str) -> ServiceImages {
+        ServiceImages {
+            app: "lenra/app/my:latest".into(),
+            devtool: format!("lenra/devtools:{}", devtool_tag),
+            postgres: "postgres:13".into(),
+            mongo: "mongo:5".into(),
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = env::temp_dir()
+            .join("lenra_cli_data_archive")
+            .join("data.tar.gz");
+        let archive = DataArchive {
+            metadata: DataMetadata::new(&images("latest"), 2),
+            mongo: vec![0, 159, 146, 150],
+            postgres: Some("DROP TABLE IF EXISTS users;".into()),
+        };
+        archive.save(&path).unwrap();
+        assert_eq!(DataArchive::load(&path).unwrap(), archive);
+
+        let archive = DataArchive {
+            postgres: None,
+            ..archive
+        };
+        archive.save(&path).unwrap();
+        assert_eq!(DataArchive::load(&path).unwrap(), archive);
+        assert_eq!(DataArchive::load_metadata(&path).unwrap(), archive.metadata);
+    }
+
+    #[test]
+    fn changed_images() {
+        let metadata = DataMetadata::new(&images("1.0.0"), DEFAULT_ENV_ID);
+        assert_eq!(metadata.changed_images(&images("1.0.0")), vec![]);
+        assert_eq!(
+            metadata.changed_images(&images("2.0.0")),
+            vec![(
+                "devtool".to_string(),
+                "lenra/devtools:1.0.0".to_string(),
+                "lenra/devtools:2.0.0".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn metadata_without_env_id() {
+        let metadata: DataMetadata = serde_yaml::from_str(
+            "cliVersion: 1.0.0\ncreatedAt: 2023-01-01T00:00:00Z\nimages: {}\n",
+        )
+        .unwrap();
+        assert_eq!(metadata.env_id, DEFAULT_ENV_ID);
+    }
+
+    #[test]
+    fn mongorestore() {
+        assert_eq!(mongorestore_command(1, 1)[5..], ["--nsInclude", "env_1.*"]);
+        assert_eq!(
+            mongorestore_command(1, 2)[5..],
+            [
+                "--nsInclude",
+                "env_1.*",
+                "--nsFrom",
+                "env_1.*",
+                "--nsTo",
+                "env_2.*"
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_database() {
+        assert_eq!(
+            drop_database_command("mongosh", "env_2"),
+            ["mongosh", "--quiet", "env_2", "--eval", "db.dropDatabase()"]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_snapshots {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn snapshot_name() {
+        let dir = Path::new(".lenra/snapshots");
+        assert_eq!(
+            snapshot_path(dir, "demo-data_1.0").unwrap(),
+            dir.join("demo-data_1.0.tar.gz")
+        );
+        assert!(snapshot_path(dir, "").is_err());
+        assert!(snapshot_path(dir, "../demo").is_err());
+        assert!(snapshot_path(dir, "my demo").is_err());
+    }
+
+    #[test]
+    fn list() {
+        let dir = env::temp_dir().join("lenra_cli_snapshots");
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(list_snapshots(&dir).unwrap(), vec![]);
+
+        fs::create_dir_all(&dir).unwrap();
+        let metadata = DataMetadata {
+            cli_version: "1.0.0".into(),
+            created_at: "2023-01-01T00:00:00Z".into(),
+            images: BTreeMap::new(),
+            env_id: DEFAULT_ENV_ID,
+        };
+        for name in ["empty", "demo"] {
+            DataArchive {
+                metadata: metadata.clone(),
+                mongo: vec![],
+                postgres: None,
+            }
+            .save(&snapshot_path(&dir, name).unwrap())
+            .unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "not a snapshot").unwrap();
+        fs::write(dir.join("broken.tar.gz"), "not an archive").unwrap();
+
+        let snapshots = list_snapshots(&dir).unwrap();
+        let names: Vec<&str> = snapshots.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["broken", "demo", "empty"]);
+        assert_eq!(snapshots[0].metadata, None);
+        assert_eq!(snapshots[1].metadata, Some(metadata));
+    }
+}
@@ -0,0 +1,151 @@
+//! The shells of the environment databases
+
+use clap::ValueEnum;
+
+use crate::{
+    devtool::app_database,
+    docker_compose::{Service, ServiceImages, DEVTOOL_DATABASE},
+};
+
+/// A database of the environment
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Database {
+    /// The app Mongo database
+    Mongo,
+    /// The devtool Postgres database
+    Postgres,
+}
+
+impl Database {
+    pub fn service(&self) -> Service {
+        match self {
+            Database::Mongo => Service::Mongo,
+            Database::Postgres => Service::Postgres,
+        }
+    }
+
+    /// The command opening an interactive shell on the database, the Mongo one being the database of the given app environment
+    pub fn shell_command(&self, images: &ServiceImages, env_id: u64) -> Vec<String> {
+        match self {
+            Database::Mongo => vec![images.mongo_shell().into(), app_database(env_id)],
+            Database::Postgres => vec![
+                "psql".into(),
+                "-U".into(),
+                "postgres".into(),
+                DEVTOOL_DATABASE.into(),
+            ],
+        }
+    }
+
+    /// The command running a query on the database and printing its result as JSON
+    pub fn eval_command(&self, images: &ServiceImages, env_id: u64, query: &str) -> Vec<String> {
+        match self {
+            Database::Mongo => vec![
+                images.mongo_shell().into(),
+                "--quiet".into(),
+                app_database(env_id),
+                "--eval".into(),
+                format!(
+                    // cursors are converted to arrays and EJSON is used when the shell provides it
+                    "var result = eval({});\nif (result && typeof result.toArray === 'function') result = result.toArray();\nprint(typeof EJSON !== 'undefined' ? EJSON.stringify(result, {{relaxed: true}}) : JSON.stringify(result));",
+                    serde_json::Value::from(query)
+                ),
+            ],
+            Database::Postgres => vec![
+                "psql".into(),
+                "-U".into(),
+                "postgres".into(),
+                "-d".into(),
+                DEVTOOL_DATABASE.into(),
+                "--no-align".into(),
+                "--tuples-only".into(),
+                "--command".into(),
+                // a CTE also accepts the data-modifying statements returning rows, unlike a subquery
+                format!(
+                    "WITH result AS ({}) SELECT coalesce(json_agg(result), '[]') FROM result",
+                    query.trim().trim_end_matches(';')
+                ),
+            ],
+        }
+    }
+
+    /// The command running a query that can not be converted to JSON, printing the plain database output.
+    /// Used for the Postgres statements returning no rows, like `SHOW` or the DDL ones.
+    pub fn plain_eval_command(&self, query: &str) -> Option<Vec<String>> {
+        match self {
+            Database::Mongo => None,
+            Database::Postgres => Some(vec![
+                "psql".into(),
+                "-U".into(),
+                "postgres".into(),
+                "-d".into(),
+                DEVTOOL_DATABASE.into(),
+                "-v".into(),
+                "ON_ERROR_STOP=1".into(),
+                "--command".into(),
+                query.into(),
+            ]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_database {
+    use super::*;
+
+    fn images(mongo: &str) -> ServiceImages {
+        ServiceImages {
+            app: "lenra/app/my:latest".into(),
+            devtool: "lenra/devtools:latest".into(),
+            postgres: "postgres:13".into(),
+            mongo: mongo.into(),
+        }
+    }
+
+    #[test]
+    fn mongo_shell() {
+        assert_eq!(
+            Database::Mongo.shell_command(&images("mongo:5"), 1),
+            vec!["mongo", "env_1"]
+        );
+        assert_eq!(
+            Database::Mongo.shell_command(&images("mongo:7"), 3),
+            vec!["mongosh", "env_3"]
+        );
+    }
+
+    #[test]
+    fn mongo_eval() {
+        let command =
+            Database::Mongo.eval_command(&images("mongo:5"), 1, r#"db.users.find({name: "John"})"#);
+        assert_eq!(command[..4], ["mongo", "--quiet", "env_1", "--eval"]);
+        assert!(command[4].starts_with(r#"var result = eval("db.users.find({name: \"John\"})");"#));
+    }
+
+    #[test]
+    fn postgres_eval() {
+        let command =
+            Database::Postgres.eval_command(&images("mongo:5"), 1, "SELECT * FROM users; ");
+        assert_eq!(
+            command.last().unwrap(),
+            "WITH result AS (SELECT * FROM users) SELECT coalesce(json_agg(result), '[]') FROM result"
+        );
+        assert_eq!(
+            Database::Postgres
+                .plain_eval_command("SHOW server_version")
+                .unwrap()[..],
+            [
+                "psql",
+                "-U",
+                "postgres",
+                "-d",
+                "lenra_devtool",
+                "-v",
+                "ON_ERROR_STOP=1",
+                "--command",
+                "SHOW server_version"
+            ]
+        );
+        assert_eq!(Database::Mongo.plain_eval_command("db.stats()"), None);
+    }
+}
@@ -0,0 +1,264 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use dofigen_lib::{Artifact, Builder};
+use lazy_static::lazy_static;
+use regex::Regex;
+use strum::Display;
+
+use crate::config::{Dofigen, Generator, LENRA_CACHE_DIRECTORY};
+
+/// The port the app is expected to listen to
+pub const DEFAULT_APP_PORT: u16 = 3000;
+
+const NODE_IMAGE: &str = "node:20-alpine";
+const RUST_BUILDER_IMAGE: &str = "rust:1-alpine";
+const PYTHON_IMAGE: &str = "python:3-slim";
+const GO_BUILDER_IMAGE: &str = "golang:1-alpine";
+const ALPINE_IMAGE: &str = "alpine:3";
+const BUILDER_NAME: &str = "builder";
+
+lazy_static! {
+    static ref CARGO_PACKAGE_NAME_REGEX: Regex =
+        Regex::new(r#"(?m)^\[package\][^\[]*?^name\s*=\s*"([^"]+)""#).unwrap();
+    static ref PACKAGE_JSON_MAIN_REGEX: Regex = Regex::new(r#""main"\s*:\s*"([^"]+)""#).unwrap();
+}
+
+/// The project kinds that can be detected from their manifest files
+#[derive(Display, Debug, PartialEq, Clone)]
+pub enum ProjectKind {
+    Node,
+    Rust,
+    Python,
+    Go,
+}
+
+impl ProjectKind {
+    /// The manifest files identifying each project kind
+    fn manifests(&self) -> &[&str] {
+        match self {
+            ProjectKind::Node => &["package.json"],
+            ProjectKind::Rust => &["Cargo.toml"],
+            ProjectKind::Python => &["pyproject.toml", "requirements.txt"],
+            ProjectKind::Go => &["go.mod"],
+        }
+    }
+}
+
+/// Detects the project kind of the given directory from its manifest files
+pub fn detect_project_kind(path: &Path) -> Option<ProjectKind> {
+    [
+        ProjectKind::Node,
+        ProjectKind::Rust,
+        ProjectKind::Python,
+        ProjectKind::Go,
+    ]
+    .into_iter()
+    .find(|kind| {
+        kind.manifests()
+            .iter()
+            .any(|manifest| path.join(manifest).is_file())
+    })
+}
+
+/// Proposes a Dofigen generator to build the given project
+pub fn propose_generator(path: &Path, kind: &ProjectKind) -> Generator {
+    let image = match kind {
+        ProjectKind::Node => {
+            let main = fs::read_to_string(path.join("package.json"))
+                .ok()
+                .and_then(|content| {
+                    PACKAGE_JSON_MAIN_REGEX
+                        .captures(content.as_str())
+                        .map(|captures| captures[1].to_string())
+                });
+            dofigen_lib::Image {
+                image: NODE_IMAGE.into(),
+                workdir: Some("/app".into()),
+                builders: Some(vec![Builder {
+                    name: Some(BUILDER_NAME.into()),
+                    image: NODE_IMAGE.into(),
+                    workdir: Some("/app".into()),
+                    adds: Some(vec!["package*.json".into()]),
+                    script: Some(vec!["npm ci --omit=dev".into()]),
+                    ..Default::default()
+                }]),
+                artifacts: Some(vec![Artifact {
+                    builder: BUILDER_NAME.into(),
+                    source: "/app/node_modules".into(),
+                    destination: "/app/node_modules".into(),
+                }]),
+                adds: Some(vec![".".into()]),
+                ignores: Some(vec!["node_modules".into()]),
+                cmd: Some(match main {
+                    Some(main) => vec!["node".into(), main],
+                    None => vec!["npm".into(), "start".into()],
+                }),
+                ..Default::default()
+            }
+        }
+        ProjectKind::Rust => {
+            let name = fs::read_to_string(path.join("Cargo.toml"))
+                .ok()
+                .and_then(|content| {
+                    CARGO_PACKAGE_NAME_REGEX
+                        .captures(content.as_str())
+                        .map(|captures| captures[1].to_string())
+                })
+                .unwrap_or("app".into());
+            dofigen_lib::Image {
+                image: ALPINE_IMAGE.into(),
+                workdir: Some("/app".into()),
+                builders: Some(vec![Builder {
+                    name: Some(BUILDER_NAME.into()),
+                    image: RUST_BUILDER_IMAGE.into(),
+                    workdir: Some("/app".into()),
+                    adds: Some(vec![".".into()]),
+                    script: Some(vec![
+                        "apk add --no-cache musl-dev".into(),
+                        "cargo build --release".into(),
+                    ]),
+                    caches: Some(vec!["/usr/local/cargo/registry".into()]),
+                    ..Default::default()
+                }]),
+                artifacts: Some(vec![Artifact {
+                    builder: BUILDER_NAME.into(),
+                    source: format!("/app/target/release/{}", name),
+                    destination: format!("/app/{}", name),
+                }]),
+                ignores: Some(vec!["target".into()]),
+                cmd: Some(vec![format!("/app/{}", name)]),
+                ..Default::default()
+            }
+        }
+        ProjectKind::Python => {
+            let install = if path.join("requirements.txt").is_file() {
+                "pip install --no-cache-dir -r requirements.txt"
+            } else {
+                "pip install --no-cache-dir ."
+            };
+            let main = ["main.py", "app.py"]
+                .into_iter()
+                .find(|file| path.join(file).is_file())
+                .unwrap_or("main.py");
+            dofigen_lib::Image {
+                image: PYTHON_IMAGE.into(),
+                workdir: Some("/app".into()),
+                adds: Some(vec![".".into()]),
+                root: Some(dofigen_lib::Root {
+                    script: Some(vec![install.into()]),
+                    ..Default::default()
+                }),
+                ignores: Some(vec!["__pycache__".into(), ".venv".into()]),
+                cmd: Some(vec!["python".into(), main.into()]),
+                ..Default::default()
+            }
+        }
+        ProjectKind::Go => dofigen_lib::Image {
+            image: ALPINE_IMAGE.into(),
+            workdir: Some("/app".into()),
+            builders: Some(vec![Builder {
+                name: Some(BUILDER_NAME.into()),
+                image: GO_BUILDER_IMAGE.into(),
+                workdir: Some("/app".into()),
+                envs: Some(HashMap::from([("CGO_ENABLED".into(), "0".into())])),
+                adds: Some(vec![".".into()]),
+                script: Some(vec!["go build -o /app/app .".into()]),
+                ..Default::default()
+            }]),
+            artifacts: Some(vec![Artifact {
+                builder: BUILDER_NAME.into(),
+                source: "/app/app".into(),
+                destination: "/app/app".into(),
+            }]),
+            cmd: Some(vec!["/app/app".into()]),
+            ..Default::default()
+        },
+    };
+    let mut ignores = image.ignores.clone().unwrap_or_default();
+    ignores.push(LENRA_CACHE_DIRECTORY.into());
+    Generator::Dofigen(Dofigen {
+        dofigen: dofigen_lib::Image {
+            ports: Some(vec![DEFAULT_APP_PORT]),
+            ignores: Some(ignores),
+            ..image
+        },
+    })
+}
+
+#[cfg(test)]
+mod test_detect_project_kind {
+    use std::{env, fs, path::PathBuf};
+
+    use super::*;
+
+    fn project_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join("lenra_cli_detect").join(name);
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        files.iter().for_each(|(file, content)| {
+            fs::write(dir.join(file), content).unwrap();
+        });
+        dir
+    }
+
+    #[test]
+    fn empty_dir() {
+        let dir = project_dir("empty", &[]);
+        assert_eq!(detect_project_kind(&dir), None);
+    }
+
+    #[test]
+    fn node_project() {
+        let dir = project_dir(
+            "node",
+            &[("package.json", r#"{"name": "app", "main": "src/index.js"}"#)],
+        );
+        let kind = detect_project_kind(&dir).unwrap();
+        assert_eq!(kind, ProjectKind::Node);
+        match propose_generator(&dir, &kind) {
+            Generator::Dofigen(dofigen) => {
+                assert_eq!(
+                    dofigen.dofigen.cmd,
+                    Some(vec!["node".into(), "src/index.js".into()])
+                );
+                assert_eq!(dofigen.dofigen.ports, Some(vec![DEFAULT_APP_PORT]));
+            }
+            generator => panic!("unexpected generator {:?}", generator),
+        }
+    }
+
+    #[test]
+    fn rust_project() {
+        let dir = project_dir(
+            "rust",
+            &[(
+                "Cargo.toml",
+                "[package]\nname = \"my-app\"\nversion = \"0.1.0\"\n\n[dependencies]\nname = \"other\"\n",
+            )],
+        );
+        let kind = detect_project_kind(&dir).unwrap();
+        assert_eq!(kind, ProjectKind::Rust);
+        match propose_generator(&dir, &kind) {
+            Generator::Dofigen(dofigen) => {
+                assert_eq!(dofigen.dofigen.cmd, Some(vec!["/app/my-app".into()]));
+            }
+            generator => panic!("unexpected generator {:?}", generator),
+        }
+    }
+
+    #[test]
+    fn python_requirements_project() {
+        let dir = project_dir("python", &[("requirements.txt", ""), ("app.py", "")]);
+        let kind = detect_project_kind(&dir).unwrap();
+        assert_eq!(kind, ProjectKind::Python);
+        match propose_generator(&dir, &kind) {
+            Generator::Dofigen(dofigen) => {
+                assert_eq!(
+                    dofigen.dofigen.cmd,
+                    Some(vec!["python".into(), "app.py".into()])
+                );
+            }
+            generator => panic!("unexpected generator {:?}", generator),
+        }
+    }
+}
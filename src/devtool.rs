@@ -1,3 +1,4 @@
+use clap::ValueEnum;
 use log::debug;
 
 use crate::{
@@ -6,18 +7,139 @@ use crate::{
     errors::Result,
 };
 
-pub async fn stop_app_env(context: &mut CommandContext) -> Result<()> {
-    debug!("Stop app environment");
+/// The id of the app environment created by the devtool
+pub const DEFAULT_ENV_ID: u64 = 1;
+
+/// The Mongo database of a devtool app environment
+pub fn app_database(env_id: u64) -> String {
+    format!("env_{}", env_id)
+}
+
+/// The log levels of the devtool
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+/// The remote procedure calls run in the devtool node
+#[derive(Clone, Debug, PartialEq)]
+pub enum DevtoolRpc {
+    /// Stops an app environment, restarted with a fresh state at the next client connection
+    StopEnv(u64),
+    /// Prints the ids of the active app environments
+    ListEnvs,
+    /// Prints the ids of the active client sessions
+    ListSessions,
+    /// Changes the log level of the devtool until its restart
+    SetLogLevel(LogLevel),
+}
+
+impl DevtoolRpc {
+    /// The Elixir expression evaluated by the devtool
+    pub fn expression(&self) -> String {
+        match self {
+            DevtoolRpc::StopEnv(env_id) => format!(
+                "ApplicationRunner.Environment.DynamicSupervisor.stop_env({})",
+                env_id
+            ),
+            DevtoolRpc::ListEnvs => list_registered_ids("ApplicationRunner.Environment.Supervisor"),
+            DevtoolRpc::ListSessions => list_registered_ids("ApplicationRunner.Session.Supervisor"),
+            DevtoolRpc::SetLogLevel(level) => format!(
+                "Logger.configure(level: :{})",
+                level.to_possible_value().unwrap().get_name()
+            ),
+        }
+    }
+}
+
+/// The expression printing the ids of the processes registered with the given supervisor module.
+/// The application runner supervisors are named `{:via, :swarm, {__MODULE__, id}}` by their `get_name/1`
+/// function, so `Swarm.registered/0` lists them as `{{module, id}, pid}` tuples.
+fn list_registered_ids(module: &str) -> String {
+    format!(
+        "Swarm.registered() |> Enum.flat_map(fn {{{{{}, id}}, _pid}} -> [id]; _ -> [] end) |> Enum.uniq() |> Enum.each(&IO.puts/1)",
+        module
+    )
+}
+
+/// Runs a remote procedure call in the devtool and returns its output
+pub async fn rpc(context: &mut CommandContext, call: &DevtoolRpc) -> Result<String> {
+    let expression = call.expression();
+    debug!("Devtool RPC: {}", expression);
     execute_compose_service_command(
         context,
         Service::Devtool,
-        &[
-            "bin/dev_tools",
-            "rpc",
-            "ApplicationRunner.Environment.DynamicSupervisor.stop_env(1)",
-        ],
+        &["bin/dev_tools", "rpc", &expression],
     )
-    .await?;
-    debug!("App environment stopped");
+    .await
+}
+
+pub async fn stop_env(context: &mut CommandContext, env_id: u64) -> Result<()> {
+    debug!("Stop app environment {}", env_id);
+    rpc(context, &DevtoolRpc::StopEnv(env_id)).await?;
+    debug!("App environment {} stopped", env_id);
     Ok(())
 }
+
+pub async fn list_envs(context: &mut CommandContext) -> Result<Vec<u64>> {
+    let output = rpc(context, &DevtoolRpc::ListEnvs).await?;
+    Ok(parse_ids(&output)
+        .iter()
+        .filter_map(|id| id.parse().ok())
+        .collect())
+}
+
+pub async fn list_sessions(context: &mut CommandContext) -> Result<Vec<String>> {
+    let output = rpc(context, &DevtoolRpc::ListSessions).await?;
+    Ok(parse_ids(&output))
+}
+
+pub async fn set_log_level(context: &mut CommandContext, level: LogLevel) -> Result<()> {
+    rpc(context, &DevtoolRpc::SetLogLevel(level)).await?;
+    Ok(())
+}
+
+/// Parses the ids printed one per line, ignoring the `:ok` result of the expression
+fn parse_ids(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != ":ok")
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod test_devtool_rpc {
+    use super::*;
+
+    #[test]
+    fn expressions() {
+        assert_eq!(
+            DevtoolRpc::StopEnv(3).expression(),
+            "ApplicationRunner.Environment.DynamicSupervisor.stop_env(3)"
+        );
+        assert_eq!(
+            DevtoolRpc::SetLogLevel(LogLevel::Warning).expression(),
+            "Logger.configure(level: :warning)"
+        );
+        assert_eq!(
+            DevtoolRpc::ListEnvs.expression(),
+            "Swarm.registered() |> Enum.flat_map(fn {{ApplicationRunner.Environment.Supervisor, id}, _pid} -> [id]; _ -> [] end) |> Enum.uniq() |> Enum.each(&IO.puts/1)"
+        );
+        assert_eq!(
+            DevtoolRpc::ListSessions.expression(),
+            "Swarm.registered() |> Enum.flat_map(fn {{ApplicationRunner.Session.Supervisor, id}, _pid} -> [id]; _ -> [] end) |> Enum.uniq() |> Enum.each(&IO.puts/1)"
+        );
+    }
+
+    #[test]
+    fn ids() {
+        assert_eq!(parse_ids("1\n12\n"), vec!["1", "12"]);
+        assert_eq!(parse_ids(""), Vec::<String>::new());
+        assert_eq!(parse_ids(":ok"), Vec::<String>::new());
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
+    path::Path,
 };
 
 use regex::Regex;
@@ -22,6 +23,32 @@ pub fn normalize_tag(tag: String) -> String {
     }
 }
 
+/// Creates a Docker Compose project name unique to the given app path
+pub fn compose_project_name(app_path: &Path) -> String {
+    let re = Regex::new(r"[^a-z0-9_-]").unwrap();
+    let dir_name = app_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let dir_name = re.replace_all(dir_name.as_str(), "-").to_string();
+    // the hash must stay stable across builds since the data volumes are scoped to the project name
+    let hash = format!("{:016x}", fnv1a_hash(app_path.to_string_lossy().as_bytes()));
+    format!(
+        "lenra-{}-{}",
+        dir_name.chars().take(40).collect::<String>(),
+        &hash[..8]
+    )
+}
+
+/// The 64 bits FNV-1a hash, whose algorithm is fixed unlike the standard library hasher
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
 #[cfg(test)]
 mod test_normalize_tag {
     use std::{
@@ -56,3 +83,33 @@ mod test_normalize_tag {
         assert_eq!(normalize_tag(tag_name), tag.to_string());
     }
 }
+
+#[cfg(test)]
+mod test_compose_project_name {
+    use std::path::Path;
+
+    use super::compose_project_name;
+
+    #[test]
+    fn unique_by_path() {
+        let name = compose_project_name(Path::new("/home/user/My App"));
+        assert!(name.starts_with("lenra-my-app-"));
+        assert_eq!(name, compose_project_name(Path::new("/home/user/My App")));
+        assert_ne!(name, compose_project_name(Path::new("/tmp/My App")));
+    }
+
+    #[test]
+    fn stable_name() {
+        assert_eq!(
+            compose_project_name(Path::new("/home/user/My App")),
+            "lenra-my-app-4f7ed8db"
+        );
+    }
+
+    #[test]
+    fn fnv1a() {
+        assert_eq!(super::fnv1a_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(super::fnv1a_hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(super::fnv1a_hash(b"foobar"), 0x85944171f73967e8);
+    }
+}
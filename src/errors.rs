@@ -4,6 +4,8 @@ use rustyline::error::ReadlineError;
 use thiserror::Error;
 use tokio::task::JoinError;
 
+use crate::ports::UsedPort;
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Error, Debug)]
@@ -28,14 +30,22 @@ pub enum Error {
     Command(#[from] CommandError),
     #[error("{0}")]
     FromUtf8(#[from] FromUtf8Error),
+    #[error("Error while watching files: {0}")]
+    Watch(#[from] notify::Error),
     // #[error("The {0} service is not exposed")]
     // ServiceNotExposed(Service),
     #[error("Some services are not started")]
     NotStartedServices,
+    #[error("Some ports to publish are already used:\n{}", .0.iter().map(|port| port.to_string()).collect::<Vec<String>>().join("\n"))]
+    UsedPorts(Vec<UsedPort>),
     #[error("The app must be built before running it")]
     NeverBuiltApp,
     #[error("The new project directory is not empty")]
     ProjectPathNotEmpty,
+    #[error("The config file {0:?} already exists")]
+    ConfigFileAlreadyExists(std::path::PathBuf),
+    #[error("Could not detect the project kind, supported projects contain one of package.json, Cargo.toml, pyproject.toml, requirements.txt or go.mod")]
+    UnknownProjectKind,
     // #[error("Check error")]
     // Check,
     #[error("The next GitHub topic is not correct: {0}")]
@@ -1,8 +1,8 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::{
     command::{create_command, get_command_output, run_command},
-    errors::Result,
+    errors::{Error, Result},
 };
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -55,6 +55,31 @@ pub async fn pull(git_dir: Option<PathBuf>) -> Result<()> {
     Ok(())
 }
 
+/// Displays the differences between two files, a missing file being considered as empty
+pub async fn diff_files(old: &Path, new: &Path) -> Result<()> {
+    let mut cmd = create_git_command();
+    cmd.arg("--no-pager")
+        .arg("diff")
+        .arg("--no-index")
+        .arg("--");
+    for path in [old, new] {
+        if path.exists() {
+            cmd.arg(path);
+        } else {
+            cmd.arg("/dev/null");
+        }
+    }
+    // the diff command exits with 1 when the files are different
+    let status = cmd.status().await?;
+    match status.code() {
+        Some(0 | 1) => Ok(()),
+        _ => Err(Error::Custom(format!(
+            "Could not display the differences between {:?} and {:?}",
+            old, new
+        ))),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Repository {
     pub name: String,
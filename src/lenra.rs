@@ -1,23 +1,40 @@
-use std::{fs, path::PathBuf};
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Component, Path, PathBuf},
+};
 
+use colored::{Color, Colorize};
 use rustyline::Editor;
 
 use crate::{
     cli::CommandContext,
     command::{get_command_output, run_command},
-    config::{DOCKERCOMPOSE_DEFAULT_PATH, LENRA_CACHE_DIRECTORY},
-    devtool::stop_app_env,
+    config::{
+        replace_config_generator, save_config_file, Application, Dev, Dockerfile, Generator,
+        DOCKERCOMPOSE_DEFAULT_PATH, DOCKERFILE_DEFAULT_PATH, LENRA_CACHE_DIRECTORY,
+        SNAPSHOTS_DEFAULT_PATH,
+    },
+    data::{self, DataArchive, DataMetadata, Snapshot},
+    db::Database,
+    detect::{detect_project_kind, propose_generator},
+    devtool::{self, LogLevel},
     docker_compose::{
-        self, compose_build, compose_down, compose_up, list_running_services, Service,
+        self, compose_build, compose_down, compose_up, list_running_services, ExecOptions,
+        LenraEnvironment, Service, ServiceImages,
     },
     errors::{Error, Result},
-    git,
+    external::external_databases,
+    git, ports, seed,
     template::{self, TemplateData},
+    users::{self, DevtoolUser},
 };
 
 #[cfg(test)]
 use mocktopus::macros::mockable;
 
+pub const EJECT_DIRECTORY: &str = "eject";
+
 #[cfg_attr(test, mockable)]
 pub async fn create_new_project(template: &str, path: &PathBuf) -> Result<()> {
     log::info!("Creating a new project");
@@ -58,6 +75,143 @@ fn create_cache_directories(path: &PathBuf, git_dir: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// Proposes an app configuration from the project files of the given directory
+pub fn propose_app_config(path: &Path) -> Result<Application> {
+    let kind = detect_project_kind(path).ok_or(Error::UnknownProjectKind)?;
+    log::info!("{} project detected", kind);
+    Ok(Application {
+        generator: propose_generator(path, &kind),
+        ..Default::default()
+    })
+}
+
+/// Initializes a Lenra app in an existing project from the given configuration
+pub fn init_project(path: &Path, config_path: &PathBuf, app: &Application) -> Result<()> {
+    log::info!("Initializing the project");
+    // validate the configuration by generating the Docker files
+    let mut context = CommandContext {
+        config_path: config_path.clone(),
+        config: Some(app.clone()),
+        ..Default::default()
+    };
+    app.generate_docker_files(&mut context, false)?;
+
+    save_config_file(config_path, app)?;
+    add_gitignore_entry(path, LENRA_CACHE_DIRECTORY)?;
+    log::info!("Project initialized");
+    Ok(())
+}
+
+/// Adds an entry to the .gitignore file of the given directory if not already ignored
+fn add_gitignore_entry(path: &Path, entry: &str) -> Result<()> {
+    let gitignore_path = path.join(".gitignore");
+    let mut content = fs::read_to_string(&gitignore_path).unwrap_or_default();
+    let already_ignored = content.lines().any(|line| {
+        let line = line.trim().trim_start_matches('/').trim_end_matches('/');
+        line == entry
+    });
+    if !already_ignored {
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(entry);
+        content.push('\n');
+        fs::write(gitignore_path, content)?;
+    }
+    Ok(())
+}
+
+/// Replaces the Dofigen generator by the Dockerfile it generates and returns the ejection warnings
+pub async fn eject_app(
+    context: &mut CommandContext,
+    dockerfile: &PathBuf,
+    dry_run: bool,
+    force: bool,
+) -> Result<Vec<String>> {
+    log::info!("Ejecting the Dofigen configuration");
+    let mut app = context
+        .config
+        .clone()
+        .ok_or(Error::Custom("The config is missing".into()))?;
+    let (dockerfile_content, dockerignore_content) = app.eject_dofigen(context)?;
+    app.generator = Generator::Dockerfile(Dockerfile {
+        docker: dockerfile.clone(),
+        watchdog: None,
+    });
+    let config_content = if context.config_path.extension() == Some(OsStr::new("json")) {
+        serde_json::to_string_pretty(&app)
+            .map_err(|error| Error::Custom(format!("Could not serialize the config: {}", error)))?
+    } else {
+        let content = fs::read_to_string(&context.config_path)
+            .map_err(|err| Error::OpenFile(err, context.config_path.clone()))?;
+        replace_config_generator(&content, &app.generator)?
+    };
+
+    let mut warnings = vec![];
+    if app
+        .dev
+        .as_ref()
+        .and_then(|dev| dev.dofigen.as_ref())
+        .is_some()
+    {
+        warnings.push(
+            "The dev.dofigen overrides of the config file are not applied to a Dockerfile generator, move them to the Dockerfile or remove them".to_string(),
+        );
+    }
+
+    let dockerfile_path = context.resolve_path(dockerfile);
+    let mut dockerignore_path = dockerfile_path.clone().into_os_string();
+    dockerignore_path.push(".dockerignore");
+    let files = [
+        (dockerfile_path.clone(), dockerfile_content),
+        (PathBuf::from(dockerignore_path), dockerignore_content),
+        (context.config_path.clone(), config_content),
+    ];
+
+    if dry_run {
+        if dockerfile_path.exists() && !force {
+            warnings.push(format!(
+                "The file {:?} already exists, the force option is needed to override it",
+                dockerfile_path
+            ));
+        }
+        let eject_dir =
+            context.resolve_path(&PathBuf::from(LENRA_CACHE_DIRECTORY).join(EJECT_DIRECTORY));
+        let root = context.resolve_path(&PathBuf::from("."));
+        for (path, content) in files {
+            let proposal = eject_dir.join(eject_proposal_path(&root, &path));
+            if let Some(parent) = proposal.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&proposal, content)?;
+            git::diff_files(&path, &proposal).await?;
+        }
+        return Ok(warnings);
+    }
+
+    if dockerfile_path.exists() && !force {
+        return Err(Error::Custom(format!(
+            "The file {:?} already exists, use the force option to override it",
+            dockerfile_path
+        )));
+    }
+    for (path, content) in files {
+        fs::write(path, content)?;
+    }
+    context.config = Some(app);
+    log::info!("Dofigen configuration ejected");
+    Ok(warnings)
+}
+
+/// The path of an eject proposal in the eject directory, relative to the project root to avoid collisions
+fn eject_proposal_path(root: &Path, path: &Path) -> PathBuf {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect()
+}
+
 pub async fn generate_app_env(context: &mut CommandContext, production: bool) -> Result<()> {
     log::info!("Generating the app environment");
     let conf = context
@@ -67,10 +221,29 @@ pub async fn generate_app_env(context: &mut CommandContext, production: bool) ->
     // TODO: check the components API version
 
     conf.generate_files(context, !production).await?;
+    for warning in get_env_images(context).await.version_warnings() {
+        eprintln!("{}", warning.color(Color::Yellow));
+    }
     Ok(())
 }
 
+/// Reads the generated Dockerfile to detect its changes
+pub fn read_generated_dockerfile(context: &mut CommandContext) -> Option<String> {
+    fs::read_to_string(context.resolve_path(&DOCKERFILE_DEFAULT_PATH.iter().collect())).ok()
+}
+
 pub async fn build_app(context: &mut CommandContext) -> Result<()> {
+    if context
+        .config
+        .as_ref()
+        .map(|conf| conf.is_prebuilt())
+        .unwrap_or(false)
+    {
+        log::info!("Pull the prebuilt Docker image");
+        docker_compose::compose_pull(context, &vec![Service::App]).await?;
+        log::info!("Image pulled");
+        return Ok(());
+    }
     log::info!("Build the Docker image");
     compose_build(context).await?;
     log::info!("Image built");
@@ -84,40 +257,610 @@ pub async fn start_env(context: &mut CommandContext) -> Result<()> {
         return Err(Error::NeverBuiltApp);
     }
 
+    ports::check_published_ports(context).await?;
+    let external_databases = external_databases(&get_dev_conf(context))?;
+    for external_database in &external_databases {
+        external_database.check_connectivity().await?;
+    }
+
     log::info!("Start the containers");
     compose_up(context).await?;
     let running_services: Vec<Service> = list_running_services(context).await?;
-    if running_services.len() < 4 {
+    // only the core services are checked, the dev services do not make the environment unusable
+    let missing_service = [
+        Service::App,
+        Service::Devtool,
+        Service::Postgres,
+        Service::Mongo,
+    ]
+    .into_iter()
+    .filter(|service| {
+        !external_databases
+            .iter()
+            .any(|database| &database.service == service)
+    })
+    .any(|service| !running_services.contains(&service));
+    if missing_service {
         return Err(Error::NotStartedServices);
     }
+    seed::apply_seed(context).await
+}
+
+/// Restarts the app container without rebuilding its image nor recreating it.
+/// The function process can't be restarted alone: the of-watchdog exits when its forked process ends.
+pub async fn restart_app(context: &mut CommandContext) -> Result<()> {
+    log::info!("Restart the app container");
+    docker_compose::compose_restart(context, &[Service::App]).await?;
     Ok(())
 }
 
-pub async fn stop_env(context: &mut CommandContext) -> Result<()> {
+pub async fn stop_env(context: &mut CommandContext, reset_data: bool) -> Result<()> {
     log::info!("Stop the containers");
-    compose_down(context).await?;
+    compose_down(context, reset_data).await?;
+    Ok(())
+}
+
+/// Removes the databases data, restarting the environment if it is running
+pub async fn reset_data(context: &mut CommandContext) -> Result<()> {
+    log::info!("Reset the environment data");
+    let running = !list_running_services(context).await?.is_empty();
+    compose_down(context, true).await?;
+    if running {
+        start_env(context).await?;
+    }
     Ok(())
 }
 
 pub async fn clear_cache(context: &mut CommandContext) -> Result<()> {
     log::info!("Clearing cache");
-    stop_app_env(context).await?;
+    clear_devtool_caches(context).await
+}
+
+/// Restarts an app environment of the devtool, with a fresh state at the next client connection
+pub async fn restart_devtool_env(context: &mut CommandContext, env_id: u64) -> Result<()> {
+    log::info!("Restart the devtool environment {}", env_id);
+    check_devtool_running(context).await?;
+    devtool::stop_env(context, env_id).await
+}
+
+/// Clears the caches of all the active app environments of the devtool, the default one being always cleared
+pub async fn clear_devtool_caches(context: &mut CommandContext) -> Result<()> {
+    log::info!("Clear the devtool caches");
+    check_devtool_running(context).await?;
+    let mut env_ids = devtool::list_envs(context).await.unwrap_or_else(|error| {
+        log::warn!(
+            "Could not list the devtool environments, only the default one is cleared: {}",
+            error
+        );
+        vec![]
+    });
+    if !env_ids.contains(&devtool::DEFAULT_ENV_ID) {
+        env_ids.insert(0, devtool::DEFAULT_ENV_ID);
+    }
+    for env_id in env_ids {
+        devtool::stop_env(context, env_id).await?;
+    }
     Ok(())
 }
 
-pub fn display_app_access_url() {
-    println!(
-        "\nApplication available at http://localhost:{}\n",
-        docker_compose::DEVTOOL_WEB_PORT
+pub async fn list_devtool_envs(context: &mut CommandContext) -> Result<Vec<u64>> {
+    check_devtool_running(context).await?;
+    devtool::list_envs(context).await
+}
+
+pub async fn list_devtool_sessions(context: &mut CommandContext) -> Result<Vec<String>> {
+    check_devtool_running(context).await?;
+    devtool::list_sessions(context).await
+}
+
+/// Changes the devtool log level until its restart
+pub async fn set_devtool_log_level(context: &mut CommandContext, level: LogLevel) -> Result<()> {
+    log::info!("Set the devtool log level to {:?}", level);
+    check_devtool_running(context).await?;
+    devtool::set_log_level(context, level).await
+}
+
+/// Lists the simulated users of the devtool
+pub async fn list_devtool_users(context: &mut CommandContext) -> Result<Vec<DevtoolUser>> {
+    check_devtool_users(context).await?;
+    users::list_users(context).await
+}
+
+/// Creates a simulated user in the devtool
+pub async fn create_devtool_user(context: &mut CommandContext, email: &str) -> Result<DevtoolUser> {
+    log::info!("Create the devtool user {}", email);
+    check_devtool_users(context).await?;
+    users::create_user(context, email).await
+}
+
+/// Makes the given user, found by its id or email, the current one of the app.
+/// The OAuth login sessions of the other users are revoked so that the browser logs in again.
+pub async fn switch_devtool_user(context: &mut CommandContext, user: &str) -> Result<DevtoolUser> {
+    log::info!("Switch to the devtool user {}", user);
+    check_devtool_users(context).await?;
+    let users = users::list_users(context).await?;
+    let user = users
+        .iter()
+        .find(|devtool_user| devtool_user.id.to_string() == user || devtool_user.email == user)
+        .cloned()
+        .ok_or(Error::Custom(format!("The user {} does not exist", user)))?;
+    let other_users = users
+        .into_iter()
+        .filter(|devtool_user| devtool_user.id != user.id)
+        .collect();
+    users::revoke_login_sessions(devtool_oauth_ports(context), other_users).await?;
+    users::save_current_user(context, &user)?;
+    Ok(user)
+}
+
+/// Requests an OAuth access token for the given user, the current one by default
+pub async fn get_devtool_user_token(
+    context: &mut CommandContext,
+    user: Option<String>,
+) -> Result<String> {
+    check_devtool_users(context).await?;
+    let user = match user.or_else(|| users::current_user_id(context).map(|id| id.to_string())) {
+        Some(user) => users::find_user(context, &user).await?,
+        None => users::list_users(context)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(Error::Custom(
+                "No devtool user, connect to the app or create one first".into(),
+            ))?,
+    };
+    log::info!(
+        "Request an access token for the devtool user {}",
+        user.email
     );
+    users::request_token(devtool_oauth_ports(context), &user).await
+}
+
+/// The host ports of the devtool OAuth server public and admin APIs
+fn devtool_oauth_ports(context: &CommandContext) -> users::OAuthPorts {
+    let port = |container_port| {
+        docker_compose::get_published_port(context, Service::Devtool, container_port)
+            .unwrap_or(container_port)
+    };
+    users::OAuthPorts {
+        public: port(docker_compose::DEVTOOL_OAUTH_PORT),
+        admin: port(docker_compose::DEVTOOL_OAUTH_ADMIN_PORT),
+    }
+}
+
+pub fn display_devtool_users(context: &CommandContext, users: &[DevtoolUser]) {
+    if users.is_empty() {
+        println!("No devtool user");
+        return;
+    }
+    let current_user = users::current_user_id(context);
+    let rows: Vec<[String; 3]> = users
+        .iter()
+        .map(|user| {
+            [
+                if current_user == Some(user.id) {
+                    "*"
+                } else {
+                    ""
+                }
+                .into(),
+                user.id.to_string(),
+                user.email.clone(),
+            ]
+        })
+        .collect();
+    let (header, lines) = format_table(&["", "ID", "EMAIL"], &rows);
+    println!("{}", header.bold());
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+/// The users are stored in the devtool Postgres database, that must run in the environment
+async fn check_devtool_users(context: &mut CommandContext) -> Result<()> {
+    check_devtool_running(context).await?;
+    if external_databases(&get_dev_conf(context))?
+        .iter()
+        .any(|database| database.service == Service::Postgres)
+    {
+        return Err(Error::Custom(
+            "The devtool users cannot be managed with an external Postgres database".into(),
+        ));
+    }
+    Ok(())
+}
+
+async fn check_devtool_running(context: &mut CommandContext) -> Result<()> {
+    if list_running_services(context)
+        .await?
+        .contains(&Service::Devtool)
+    {
+        Ok(())
+    } else {
+        Err(Error::NotStartedServices)
+    }
 }
 
-pub async fn update_env_images(
+pub fn display_app_access_url(context: &CommandContext) {
+    let port = docker_compose::get_published_port(
+        context,
+        Service::Devtool,
+        docker_compose::DEVTOOL_WEB_PORT,
+    )
+    .unwrap_or(docker_compose::DEVTOOL_WEB_PORT);
+    println!("\nApplication available at http://localhost:{}\n", port);
+}
+
+/// Exports the databases data of the running environment to an archive, the Mongo data being the one of the given app environment
+pub async fn export_data(
     context: &mut CommandContext,
-    services: &Vec<Service>,
+    path: &Path,
+    with_devtool: bool,
+    env_id: u64,
 ) -> Result<()> {
+    log::info!("Export the environment data to {:?}", path);
+    let mut databases = vec![Service::Mongo];
+    if with_devtool {
+        databases.push(Service::Postgres);
+    }
+    check_container_databases(context, &databases).await?;
+    let images = get_env_images(context).await;
+    let metadata = DataMetadata::new(&images, env_id);
+    let archive = DataArchive::dump(context, metadata, with_devtool).await?;
+    archive.save(path)
+}
+
+/// Imports the databases data of an archive to the running environment, the Mongo data going to the given app environment.
+/// Returns the warnings about the images that changed since the export.
+pub async fn import_data(
+    context: &mut CommandContext,
+    path: &Path,
+    env_id: u64,
+) -> Result<Vec<String>> {
+    log::info!("Import the environment data from {:?}", path);
+    let archive = DataArchive::load(path)?;
+    let mut databases = vec![Service::Mongo];
+    if archive.postgres.is_some() {
+        databases.push(Service::Postgres);
+    }
+    check_container_databases(context, &databases).await?;
+    let images = get_env_images(context).await;
+    let warnings = archive
+        .metadata
+        .changed_images(&images)
+        .into_iter()
+        .map(|(service, archived, current)| {
+            format!(
+                "The data has been exported with the {} image {} but the environment uses {}",
+                service, archived, current
+            )
+        })
+        .collect();
+    archive
+        .restore(context, env_id, images.mongo_shell())
+        .await?;
+    // the app environment is stopped to use the imported data
+    devtool::stop_env(context, env_id).await?;
+    Ok(warnings)
+}
+
+/// Displays warnings once the loaders have ended
+pub fn display_warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("{}", warning.color(Color::Yellow));
+    }
+}
+
+/// Opens an interactive shell on a database of the running environment, or prints the JSON result of a query
+pub async fn db_shell(
+    context: &mut CommandContext,
+    database: Database,
+    env_id: u64,
+    query: Option<&str>,
+) -> Result<()> {
+    let service = database.service();
+    check_container_databases(context, std::slice::from_ref(&service)).await?;
+    let images = get_env_images(context).await;
+    match query {
+        Some(query) => {
+            let command = database.eval_command(&images, env_id, query);
+            let command: Vec<&str> = command.iter().map(String::as_str).collect();
+            let output = match docker_compose::execute_compose_service_command_with_input(
+                context,
+                service.clone(),
+                &command,
+                None,
+            )
+            .await
+            {
+                Ok(output) => output,
+                // the statements returning no rows can not be converted to JSON and have not been run
+                Err(error) => match database.plain_eval_command(query) {
+                    Some(command) => {
+                        log::debug!("Run the query without JSON conversion: {}", error);
+                        let command: Vec<&str> = command.iter().map(String::as_str).collect();
+                        docker_compose::execute_compose_service_command_with_input(
+                            context, service, &command, None,
+                        )
+                        .await?
+                    }
+                    None => return Err(error),
+                },
+            };
+            println!("{}", String::from_utf8_lossy(&output).trim_end());
+        }
+        None => {
+            let command = database.shell_command(&images, env_id);
+            let command: Vec<&str> = command.iter().map(String::as_str).collect();
+            docker_compose::compose_exec(context, service, &command, &ExecOptions::default())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The command opening the best shell available in a container
+const SHELL_COMMAND: [&str; 3] = [
+    "sh",
+    "-c",
+    "if command -v bash > /dev/null; then exec bash; else exec sh; fi",
+];
+
+/// Executes a command in a service of the running environment, opening a shell when no command is given
+pub async fn exec_service(
+    context: &mut CommandContext,
+    service: Service,
+    command: &[String],
+    options: &ExecOptions,
+) -> Result<()> {
+    check_services(context, std::slice::from_ref(&service))?;
+    if !list_running_services(context).await?.contains(&service) {
+        return Err(Error::Custom(format!(
+            "The {} service is not running",
+            service
+        )));
+    }
+    let command: Vec<&str> = if command.is_empty() {
+        SHELL_COMMAND.to_vec()
+    } else {
+        command.iter().map(String::as_str).collect()
+    };
+    docker_compose::compose_exec(context, service, &command, options).await
+}
+
+fn get_snapshots_dir(context: &CommandContext) -> PathBuf {
+    context.resolve_path(&SNAPSHOTS_DEFAULT_PATH.iter().collect())
+}
+
+/// Saves the databases data of the running environment, including the devtool ones, as a named snapshot
+pub async fn save_snapshot(
+    context: &mut CommandContext,
+    name: &str,
+    force: bool,
+    env_id: u64,
+) -> Result<()> {
+    let path = data::snapshot_path(&get_snapshots_dir(context), name)?;
+    if path.exists() && !force {
+        return Err(Error::Custom(format!(
+            "The snapshot {} already exists, use the --force option to replace it",
+            name
+        )));
+    }
+    export_data(context, &path, true, env_id).await
+}
+
+/// Replaces the databases data of the running environment by the ones of a snapshot
+pub async fn restore_snapshot(
+    context: &mut CommandContext,
+    name: &str,
+    env_id: u64,
+) -> Result<Vec<String>> {
+    let path = get_existing_snapshot_path(context, name)?;
+    import_data(context, &path, env_id).await
+}
+
+pub fn delete_snapshots(context: &CommandContext, names: &[String]) -> Result<()> {
+    let paths = names
+        .iter()
+        .map(|name| get_existing_snapshot_path(context, name))
+        .collect::<Result<Vec<PathBuf>>>()?;
+    for path in paths {
+        log::info!("Delete the snapshot {:?}", path);
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+fn get_existing_snapshot_path(context: &CommandContext, name: &str) -> Result<PathBuf> {
+    let path = data::snapshot_path(&get_snapshots_dir(context), name)?;
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(Error::Custom(format!(
+            "The snapshot {} does not exist",
+            name
+        )))
+    }
+}
+
+pub fn list_snapshots(context: &CommandContext) -> Result<Vec<Snapshot>> {
+    data::list_snapshots(&get_snapshots_dir(context))
+}
+
+pub fn display_snapshots(snapshots: &[Snapshot]) {
+    if snapshots.is_empty() {
+        println!("No snapshot found");
+        return;
+    }
+    let rows: Vec<[String; 4]> = snapshots
+        .iter()
+        .map(|snapshot| {
+            let (created_at, cli_version) = snapshot
+                .metadata
+                .as_ref()
+                .map(|metadata| (metadata.created_at.clone(), metadata.cli_version.clone()))
+                .unwrap_or_else(|| ("unreadable".into(), String::new()));
+            [
+                snapshot.name.clone(),
+                created_at,
+                format!("{:.1} MB", snapshot.size as f64 / 1_000_000.0),
+                cli_version,
+            ]
+        })
+        .collect();
+    let (header, lines) = format_table(&["NAME", "CREATED", "SIZE", "CLI VERSION"], &rows);
+    println!("{}", header.bold());
+    for (line, snapshot) in lines.into_iter().zip(snapshots) {
+        if snapshot.metadata.is_none() {
+            println!("{}", line.color(Color::Yellow));
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Checks that the given databases run in the containers of the running environment
+async fn check_container_databases(
+    context: &mut CommandContext,
+    databases: &[Service],
+) -> Result<()> {
+    if let Some(external) = external_databases(&get_dev_conf(context))?
+        .into_iter()
+        .find(|external| databases.contains(&external.service))
+    {
+        return Err(Error::Custom(format!(
+            "The {} database is external, this command only works with the environment database containers",
+            external.service
+        )));
+    }
+    let running_services = list_running_services(context).await?;
+    if databases
+        .iter()
+        .all(|database| running_services.contains(database))
+    {
+        Ok(())
+    } else {
+        Err(Error::NotStartedServices)
+    }
+}
+
+/// Checks that the given services are Lenra services or dev services of the app config
+pub fn check_services(context: &CommandContext, services: &[Service]) -> Result<()> {
+    docker_compose::check_services(&get_dev_conf(context), services)
+}
+
+fn get_dev_conf(context: &CommandContext) -> Option<Dev> {
+    context.config.as_ref().and_then(|app| app.dev.clone())
+}
+
+async fn get_env_images(context: &CommandContext) -> ServiceImages {
+    docker_compose::get_services_images(&get_dev_conf(context)).await
+}
+
+/// Lists the Lenra environments of the machine
+pub async fn list_environments() -> Result<Vec<LenraEnvironment>> {
+    log::info!("List the Lenra environments");
+    docker_compose::list_lenra_environments().await
+}
+
+pub fn display_environments(environments: &[LenraEnvironment]) {
+    if environments.is_empty() {
+        println!("No Lenra environment found");
+        return;
+    }
+    let rows: Vec<[String; 5]> = environments
+        .iter()
+        .map(|environment| {
+            let total = environment.running_services.len() + environment.stopped_services.len();
+            [
+                environment.project_path.display().to_string(),
+                environment.app_name.clone(),
+                if total == 0 {
+                    "data only".to_string()
+                } else {
+                    format!("{}/{} running", environment.running_services.len(), total)
+                },
+                environment
+                    .web_port
+                    .filter(|_| !environment.running_services.is_empty())
+                    .map(|port| format!("http://localhost:{}", port))
+                    .unwrap_or_default(),
+                environment.cli_version.clone(),
+            ]
+        })
+        .collect();
+    let (header, lines) = format_table(&["PROJECT", "APP", "STATUS", "URL", "CLI VERSION"], &rows);
+    println!("{}", header.bold());
+    for (line, environment) in lines.into_iter().zip(environments) {
+        if environment.is_stale() {
+            println!("{}", line.color(Color::Yellow));
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Aligns the columns of a table and returns its header and rows lines
+fn format_table<const N: usize>(header: &[&str; N], rows: &[[String; N]]) -> (String, Vec<String>) {
+    let header = header.map(String::from);
+    let widths: Vec<usize> = (0..N)
+        .map(|i| {
+            rows.iter()
+                .chain([&header])
+                .map(|row| row[i].len())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let format_row = |row: &[String; N]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:width$}", value, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    (format_row(&header), rows.iter().map(format_row).collect())
+}
+
+/// Stops and removes the given Lenra environments.
+/// The data of the environments whose project has been removed is removed too.
+pub async fn stop_environments(environments: &[LenraEnvironment], verbose: bool) -> Result<()> {
+    for environment in environments {
+        log::info!("Stop the {} environment", environment.project_name);
+        // the data is only removed with the project
+        let remove_volumes = !environment.project_path.exists();
+        if !environment.running_services.is_empty() || !environment.stopped_services.is_empty() {
+            docker_compose::compose_down_project(
+                &environment.project_name,
+                remove_volumes,
+                verbose,
+            )
+            .await?;
+        }
+        if remove_volumes {
+            docker_compose::remove_volumes(&environment.volumes, verbose).await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn update_env_images(context: &mut CommandContext, services: &[Service]) -> Result<()> {
     log::info!("Update the environment images");
-    docker_compose::compose_pull(context, services).await?;
+    check_services(context, services)?;
+    // the external databases have no container
+    let external_databases = external_databases(&get_dev_conf(context))?;
+    let services: Vec<Service> = services
+        .iter()
+        .filter(|service| {
+            !external_databases
+                .iter()
+                .any(|external| &external.service == *service)
+        })
+        .cloned()
+        .collect();
+    docker_compose::compose_pull(context, &services).await?;
     Ok(())
 }
 
@@ -220,13 +963,15 @@ pub async fn upgrade_app(context: &mut CommandContext) -> Result<()> {
 }
 
 fn confirm_checkout() -> Result<bool> {
-    let mut rl = Editor::<()>::new()?;
     println!("There is no template last commit in this project, the template files will checked out to your app.\nMake sure your project is saved (for example with git).");
+    confirm("Checkout the template ? [y/N] ")
+}
+
+/// Asks the user a yes/no question, the default answer being no
+pub fn confirm(question: &str) -> Result<bool> {
+    let mut rl = Editor::<()>::new()?;
     loop {
-        let res = rl
-            .readline("Checkout the template ? [y/N] ")?
-            .trim()
-            .to_lowercase();
+        let res = rl.readline(question)?.trim().to_lowercase();
         if res == "y" || res == "yes" {
             return Ok(true);
         } else if res.is_empty() || res == "n" || res == "no" {
@@ -234,3 +979,25 @@ fn confirm_checkout() -> Result<bool> {
         }
     }
 }
+
+#[cfg(test)]
+mod eject_tests {
+    use super::*;
+
+    #[test]
+    fn proposal_paths() {
+        let root = Path::new("/home/user/app");
+        assert_eq!(
+            eject_proposal_path(root, Path::new("/home/user/app/Dockerfile")),
+            PathBuf::from("Dockerfile")
+        );
+        assert_eq!(
+            eject_proposal_path(root, Path::new("/home/user/app/docker/Dockerfile")),
+            PathBuf::from("docker/Dockerfile")
+        );
+        assert_eq!(
+            eject_proposal_path(root, Path::new("/home/user/Dockerfile")),
+            PathBuf::from("home/user/Dockerfile")
+        );
+    }
+}
@@ -9,16 +9,24 @@ use env_logger;
 mod cli;
 mod command;
 mod config;
+mod data;
+mod db;
+mod detect;
 mod devtool;
 mod docker;
 mod docker_compose;
 mod errors;
+mod external;
 mod git;
 mod github;
 mod keyboard_event;
 mod lenra;
 mod matching;
+mod ports;
+mod seed;
 mod template;
+mod users;
+mod watcher;
 
 #[tokio::main]
 async fn main() -> () {
@@ -0,0 +1,246 @@
+//! Checks that the ports published by the environment are free before starting it
+
+use std::{fmt::Display, path::PathBuf};
+
+use log::debug;
+
+use crate::{
+    cli::CommandContext,
+    command::{create_command, get_command_output},
+    docker::compose_project_name,
+    docker_compose::{
+        free_port, get_published_ports, is_address_port_free, PublishedPort, PROJECT_PATH_LABEL,
+    },
+    errors::{Error, Result},
+};
+
+/// What is using a host port
+#[derive(Clone, Debug, PartialEq)]
+pub enum PortOwner {
+    /// Another Lenra environment, from its project path
+    Environment(PathBuf),
+    /// A Docker container, from its name
+    Container(String),
+    /// A host process
+    Process {
+        pid: String,
+        name: String,
+    },
+    Unknown,
+}
+
+/// A port to publish already used on the host
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsedPort {
+    pub port: PublishedPort,
+    pub owner: PortOwner,
+    /// A free host port to suggest
+    pub free_port: Option<u16>,
+}
+
+impl Display for UsedPort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "  - the {} port {} can not be published on the port {}",
+            self.port.service, self.port.container_port, self.port.host_port
+        )?;
+        match &self.owner {
+            PortOwner::Environment(path) => {
+                return write!(
+                    f,
+                    ", used by the Lenra environment of {}. Stop it running `lenra stop` in its directory or `lenra ls --stop-all`",
+                    path.display()
+                )
+            }
+            PortOwner::Container(name) => write!(f, ", used by the {} container", name)?,
+            PortOwner::Process { pid, name } => {
+                write!(f, ", used by the {} process (PID {})", name, pid)?
+            }
+            PortOwner::Unknown => write!(f, ", already used")?,
+        }
+        let action = match self.owner {
+            PortOwner::Unknown => "Free it",
+            _ => "Stop it",
+        };
+        match self.free_port {
+            Some(free_port) => write!(
+                f,
+                ". {} or bind another port with `--expose {}={}:{}`",
+                action, self.port.service, free_port, self.port.container_port
+            ),
+            None => write!(f, ". {} or bind another port", action),
+        }
+    }
+}
+
+/// A container publishing ports on the host
+struct PortsContainer {
+    name: String,
+    project_name: String,
+    project_path: Option<PathBuf>,
+    host_ports: Vec<u16>,
+}
+
+/// Checks that the ports to publish are not used by something else than the app environment
+pub async fn check_published_ports(context: &mut CommandContext) -> Result<()> {
+    let project_name = compose_project_name(&context.resolve_path(&PathBuf::from(".")));
+    let ports = get_published_ports(context)?;
+    let containers = list_ports_containers().await.unwrap_or_else(|error| {
+        debug!("Could not list the Docker containers: {}", error);
+        vec![]
+    });
+
+    // the suggested ports must differ from the published ones, the ones of the containers and the other suggestions
+    let mut taken_ports: Vec<u16> = ports
+        .iter()
+        .map(|port| port.host_port)
+        .chain(
+            containers
+                .iter()
+                .flat_map(|container| container.host_ports.iter().copied()),
+        )
+        .collect();
+    let mut used_ports = vec![];
+    for port in ports {
+        let owner = match containers
+            .iter()
+            .find(|container| container.host_ports.contains(&port.host_port))
+        {
+            // the app environment is already started
+            Some(container) if container.project_name == project_name => continue,
+            Some(container) => match &container.project_path {
+                Some(path) => PortOwner::Environment(path.clone()),
+                None => PortOwner::Container(container.name.clone()),
+            },
+            None if is_address_port_free(port.address, port.host_port) => continue,
+            None => find_port_process(port.host_port)
+                .await
+                .unwrap_or(PortOwner::Unknown),
+        };
+        let free_port = free_port(&taken_ports);
+        taken_ports.extend(free_port);
+        used_ports.push(UsedPort {
+            free_port,
+            port,
+            owner,
+        });
+    }
+    if used_ports.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::UsedPorts(used_ports))
+    }
+}
+
+async fn list_ports_containers() -> Result<Vec<PortsContainer>> {
+    let output = get_command_output(
+        create_command("docker").arg("ps").arg("--format").arg(format!(
+            "{{{{.Names}}}}\t{{{{.Label \"com.docker.compose.project\"}}}}\t{{{{.Label \"{}\"}}}}\t{{{{.Ports}}}}",
+            PROJECT_PATH_LABEL
+        )),
+    )
+    .await?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields[..] {
+                [name, project_name, project_path, ports] => Some(PortsContainer {
+                    name: name.into(),
+                    project_name: project_name.into(),
+                    project_path: Some(project_path)
+                        .filter(|path| !path.is_empty())
+                        .map(PathBuf::from),
+                    host_ports: parse_host_ports(ports),
+                }),
+                _ => None,
+            }
+        })
+        .collect())
+}
+
+/// Gets the host ports from the Docker ports description (e.g. `0.0.0.0:4000->4000/tcp, :::4000->4000/tcp`)
+fn parse_host_ports(ports: &str) -> Vec<u16> {
+    let mut host_ports = vec![];
+    ports
+        .split(", ")
+        .filter_map(|binding| binding.split_once("->"))
+        .filter_map(|(host, _)| host.rsplit(':').next()?.parse().ok())
+        .for_each(|port| {
+            if !host_ports.contains(&port) {
+                host_ports.push(port);
+            }
+        });
+    host_ports
+}
+
+/// Finds the process listening on a port with lsof
+async fn find_port_process(port: u16) -> Option<PortOwner> {
+    let output = get_command_output(
+        create_command("lsof")
+            .arg("-nP")
+            .arg(format!("-iTCP:{}", port))
+            .arg("-sTCP:LISTEN")
+            .arg("-Fpc"),
+    )
+    .await
+    .ok()?;
+    parse_lsof_output(&output)
+}
+
+fn parse_lsof_output(output: &str) -> Option<PortOwner> {
+    let pid = output.lines().find_map(|line| line.strip_prefix('p'))?;
+    let name = output
+        .lines()
+        .find_map(|line| line.strip_prefix('c'))
+        .unwrap_or_default();
+    Some(PortOwner::Process {
+        pid: pid.into(),
+        name: name.into(),
+    })
+}
+
+#[cfg(test)]
+mod test_port_owners {
+    use super::*;
+
+    #[test]
+    fn host_ports() {
+        assert_eq!(
+            parse_host_ports("0.0.0.0:14000->4000/tcp, :::14000->4000/tcp, 27017/tcp"),
+            vec![14000]
+        );
+        assert_eq!(parse_host_ports(""), Vec::<u16>::new());
+    }
+
+    #[test]
+    fn lsof_output() {
+        assert_eq!(
+            parse_lsof_output("p1234\ncnode\nf23\n"),
+            Some(PortOwner::Process {
+                pid: "1234".into(),
+                name: "node".into()
+            })
+        );
+        assert_eq!(parse_lsof_output(""), None);
+    }
+
+    #[test]
+    fn used_port_message() {
+        let used_port = UsedPort {
+            port: PublishedPort {
+                service: "devtool".into(),
+                address: None,
+                host_port: 4000,
+                container_port: 4000,
+            },
+            owner: PortOwner::Container("redis".into()),
+            free_port: Some(14000),
+        };
+        assert_eq!(
+            used_port.to_string(),
+            "  - the devtool port 4000 can not be published on the port 4000, used by the redis container. Stop it or bind another port with `--expose devtool=14000:4000`"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Applies the seed files and the migration scripts to the app database.
+//! Each applied script is recorded in a collection so it is only applied once.
+
+use std::{fs, path::PathBuf};
+
+use colored::{Color, Colorize};
+use log::{debug, info};
+
+use crate::{
+    cli::CommandContext,
+    config::DevSeed,
+    devtool::{app_database, DEFAULT_ENV_ID},
+    docker_compose::{execute_compose_service_command_with_input, get_services_images, Service},
+    errors::{Error, Result},
+    external::external_databases,
+};
+
+/// The collection recording the applied seed files and migration scripts
+const MIGRATIONS_COLLECTION: &str = "_lenra_migrations";
+/// The path of the script in the Mongo container
+const SCRIPT_PATH: &str = "/tmp/lenra-seed.js";
+
+/// A seed file or migration script to apply
+#[derive(Debug, PartialEq, Clone)]
+pub struct SeedStep {
+    /// The identifier recorded once the step applied
+    pub id: String,
+    pub path: PathBuf,
+}
+
+impl SeedStep {
+    /// Returns the Mongo shell script applying the step and recording it
+    pub fn to_script(&self, content: &str) -> Result<String> {
+        let script = match self.path.extension().and_then(|ext| ext.to_str()) {
+            Some("js") => content.to_string(),
+            // a JSON seed file maps collection names to their documents
+            Some("json") => {
+                let seed: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_str(content).map_err(|error| {
+                        Error::Custom(format!(
+                            "The seed file {:?} is not valid: {}",
+                            self.path, error
+                        ))
+                    })?;
+                format!(
+                    "const seed = {};\nObject.keys(seed).forEach(function (name) {{\n  if (seed[name].length) db.getCollection(name).insertMany(seed[name]);\n}});",
+                    serde_json::Value::Object(seed)
+                )
+            }
+            _ => {
+                return Err(Error::Custom(format!(
+                    "The seed file {:?} must be a .json or .js file",
+                    self.path
+                )))
+            }
+        };
+        Ok(format!(
+            "{}\ndb.getCollection({}).insertOne({{_id: {}, appliedAt: new Date()}});\n",
+            script,
+            serde_json::Value::from(MIGRATIONS_COLLECTION),
+            serde_json::Value::from(self.id.as_str())
+        ))
+    }
+}
+
+/// Lists the seed files then the migration scripts in the order they are applied
+pub fn seed_steps(seed: &DevSeed) -> Vec<SeedStep> {
+    let steps = |kind: &str, paths: &Option<Vec<PathBuf>>| {
+        paths
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| SeedStep {
+                id: format!("{}:{}", kind, path.display()),
+                path,
+            })
+            .collect::<Vec<SeedStep>>()
+    };
+    let mut all = steps("seed", &seed.files);
+    all.extend(steps("migration", &seed.migrations));
+    all
+}
+
+/// Applies the seed steps not applied yet to the app database of the running environment
+pub async fn apply_seed(context: &mut CommandContext) -> Result<()> {
+    let seed = match context
+        .config
+        .as_ref()
+        .and_then(|app| app.dev.as_ref())
+        .and_then(|dev| dev.seed.clone())
+    {
+        Some(seed) => seed,
+        None => return Ok(()),
+    };
+    let dev_conf = context.config.as_ref().and_then(|app| app.dev.clone());
+    if external_databases(&dev_conf)?
+        .iter()
+        .any(|external| external.service == Service::Mongo)
+    {
+        eprintln!(
+            "{}",
+            "The seed files are not applied to the external Mongo database".color(Color::Yellow)
+        );
+        return Ok(());
+    }
+    let mongo_shell = get_services_images(&dev_conf).await.mongo_shell();
+    let database = app_database(seed.env_id.unwrap_or(DEFAULT_ENV_ID));
+    let applied = list_applied_steps(context, mongo_shell, &database).await?;
+    for step in seed_steps(&seed) {
+        if applied.contains(&step.id) {
+            debug!("The {} seed step is already applied", step.id);
+            continue;
+        }
+        info!("Apply the {} seed step", step.id);
+        let path = context.resolve_path(&step.path);
+        let content = fs::read_to_string(&path).map_err(|err| Error::OpenFile(err, path))?;
+        run_script(context, mongo_shell, &database, step.to_script(&content)?).await?;
+    }
+    Ok(())
+}
+
+async fn list_applied_steps(
+    context: &mut CommandContext,
+    mongo_shell: &str,
+    database: &str,
+) -> Result<Vec<String>> {
+    let script = format!(
+        "db.getCollection({}).find({{}}, {{_id: 1}}).forEach(function (step) {{ print(step._id); }});",
+        serde_json::Value::from(MIGRATIONS_COLLECTION)
+    );
+    let output = run_script(context, mongo_shell, database, script).await?;
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+/// Runs a script file in the Mongo container, the shell exiting with an error if the script fails
+async fn run_script(
+    context: &mut CommandContext,
+    mongo_shell: &str,
+    database: &str,
+    script: String,
+) -> Result<Vec<u8>> {
+    execute_compose_service_command_with_input(
+        context,
+        Service::Mongo,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cat > {path} && {} --quiet {} {path}",
+                mongo_shell,
+                database,
+                path = SCRIPT_PATH
+            ),
+        ],
+        Some(script.into_bytes()),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod test_seed {
+    use super::*;
+
+    #[test]
+    fn steps_order() {
+        let seed = DevSeed {
+            files: Some(vec!["seed/users.json".into()]),
+            migrations: Some(vec!["migrations/001.js".into(), "migrations/002.js".into()]),
+            ..Default::default()
+        };
+        let ids: Vec<String> = seed_steps(&seed).into_iter().map(|step| step.id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "seed:seed/users.json",
+                "migration:migrations/001.js",
+                "migration:migrations/002.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn js_script() {
+        let step = SeedStep {
+            id: "migration:migrations/001.js".into(),
+            path: "migrations/001.js".into(),
+        };
+        assert_eq!(
+            step.to_script("db.users.updateMany({}, {$set: {active: true}});")
+                .unwrap(),
+            "db.users.updateMany({}, {$set: {active: true}});\ndb.getCollection(\"_lenra_migrations\").insertOne({_id: \"migration:migrations/001.js\", appliedAt: new Date()});\n"
+        );
+    }
+
+    #[test]
+    fn json_script() {
+        let step = SeedStep {
+            id: "seed:users.json".into(),
+            path: "users.json".into(),
+        };
+        let script = step.to_script(r#"{"users": [{"name": "John"}]}"#).unwrap();
+        assert!(script.starts_with(r#"const seed = {"users":[{"name":"John"}]};"#));
+        assert!(step.to_script("[]").is_err());
+
+        let step = SeedStep {
+            id: "seed:users.csv".into(),
+            path: "users.csv".into(),
+        };
+        assert!(step.to_script("name\nJohn").is_err());
+    }
+}
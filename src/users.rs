@@ -0,0 +1,430 @@
+//! The simulated users of the devtool
+
+use std::{fs, path::PathBuf};
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use log::debug;
+use regex::Regex;
+use serde::Deserialize;
+use url::Url;
+
+use crate::{
+    cli::CommandContext,
+    config::LENRA_CACHE_DIRECTORY,
+    docker_compose::{execute_compose_service_command, Service, DEVTOOL_DATABASE},
+    errors::{Error, Result},
+};
+
+/// The file storing the id of the current user of the app
+pub const CURRENT_USER_PATH: [&str; 2] = [LENRA_CACHE_DIRECTORY, "user"];
+/// The OAuth client registered by the CLI to request access tokens
+pub const OAUTH_CLIENT_ID: &str = "lenra-cli";
+const OAUTH_REDIRECT_URI: &str = "http://localhost/lenra-cli/callback";
+const OAUTH_SCOPES: &str = "app:websocket";
+
+lazy_static! {
+    static ref EMAIL_REGEX: Regex = Regex::new(r"^[^@\s']+@[^@\s']+$").unwrap();
+}
+
+/// A user of the devtool
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct DevtoolUser {
+    pub id: u64,
+    pub email: String,
+}
+
+/// The query listing the devtool users
+fn list_users_query() -> String {
+    "SELECT id, email FROM users ORDER BY id".into()
+}
+
+/// The query creating a devtool user and returning it
+fn create_user_query(email: &str) -> Result<String> {
+    if !EMAIL_REGEX.is_match(email) {
+        return Err(Error::Custom(format!("Invalid email: {}", email)));
+    }
+    Ok(format!(
+        "INSERT INTO users (email, inserted_at, updated_at) VALUES ('{}', now(), now()) RETURNING id, email",
+        email
+    ))
+}
+
+/// Runs a query returning users in the devtool database
+async fn query_users(context: &mut CommandContext, query: &str) -> Result<Vec<DevtoolUser>> {
+    debug!("Users query: {}", query);
+    // a CTE is needed since data-modifying statements are not allowed in subqueries
+    let query = format!(
+        "WITH result AS ({}) SELECT coalesce(json_agg(result), '[]') FROM result",
+        query
+    );
+    let output = execute_compose_service_command(
+        context,
+        Service::Postgres,
+        &[
+            "psql",
+            "-U",
+            "postgres",
+            "-d",
+            DEVTOOL_DATABASE,
+            "--no-align",
+            "--tuples-only",
+            "--command",
+            &query,
+        ],
+    )
+    .await?;
+    parse_users(&output)
+}
+
+fn parse_users(output: &str) -> Result<Vec<DevtoolUser>> {
+    serde_json::from_str(output.trim())
+        .map_err(|error| Error::Custom(format!("Could not parse the devtool users: {}", error)))
+}
+
+pub async fn list_users(context: &mut CommandContext) -> Result<Vec<DevtoolUser>> {
+    query_users(context, &list_users_query()).await
+}
+
+pub async fn create_user(context: &mut CommandContext, email: &str) -> Result<DevtoolUser> {
+    query_users(context, &create_user_query(email)?)
+        .await?
+        .pop()
+        .ok_or(Error::Custom(format!(
+            "Could not create the user {}",
+            email
+        )))
+}
+
+/// Finds a user by its id or its email
+pub async fn find_user(context: &mut CommandContext, user: &str) -> Result<DevtoolUser> {
+    list_users(context)
+        .await?
+        .into_iter()
+        .find(|devtool_user| devtool_user.id.to_string() == user || devtool_user.email == user)
+        .ok_or(Error::Custom(format!("The user {} does not exist", user)))
+}
+
+pub fn current_user_id(context: &CommandContext) -> Option<u64> {
+    let path = context.resolve_path(&CURRENT_USER_PATH.iter().collect());
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| content.trim().parse().ok())
+}
+
+pub fn save_current_user(context: &CommandContext, user: &DevtoolUser) -> Result<()> {
+    let path: PathBuf = context.resolve_path(&CURRENT_USER_PATH.iter().collect());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, user.id.to_string())?;
+    Ok(())
+}
+
+/// The host ports of the devtool OAuth server, an Ory Hydra instance
+#[derive(Clone, Copy, Debug)]
+pub struct OAuthPorts {
+    pub public: u16,
+    pub admin: u16,
+}
+
+impl OAuthPorts {
+    fn public_url(&self) -> String {
+        format!("http://localhost:{}", self.public)
+    }
+
+    fn admin_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.admin)
+    }
+}
+
+#[derive(Deserialize)]
+struct RedirectResponse {
+    redirect_to: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// The cookies of the OAuth server, needed by its CSRF checks between the redirections
+#[derive(Default)]
+struct Cookies(Vec<(String, String)>);
+
+impl Cookies {
+    fn store(&mut self, set_cookie: &str) {
+        let pair = set_cookie.split(';').next().unwrap_or_default();
+        if let Some((name, value)) = pair.split_once('=') {
+            let name = name.trim().to_string();
+            self.0.retain(|(cookie, _)| cookie != &name);
+            self.0.push((name, value.trim().to_string()));
+        }
+    }
+
+    fn header(&self) -> String {
+        self.0
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<String>>()
+            .join("; ")
+    }
+}
+
+/// Requests an access token for the given user with the authorization code flow of the devtool OAuth server.
+/// The login and consent requests are accepted for the user through the Hydra admin API,
+/// the CLI registering its own public OAuth client.
+pub async fn request_token(ports: OAuthPorts, user: &DevtoolUser) -> Result<String> {
+    let subject = user.id.to_string();
+    tokio::task::spawn_blocking(move || {
+        let agent = ureq::AgentBuilder::new().redirects(0).build();
+        register_client(&agent, &ports)?;
+        let mut cookies = Cookies::default();
+        let mut get = |url: &str| -> Result<String> {
+            let url = rebase_url(&ports.public_url(), url)?;
+            let response = agent.get(&url).set("Cookie", &cookies.header()).call()?;
+            response
+                .all("set-cookie")
+                .into_iter()
+                .for_each(|set_cookie| cookies.store(set_cookie));
+            response
+                .header("location")
+                .map(String::from)
+                .ok_or(Error::Custom(format!(
+                    "The devtool OAuth server did not redirect the request {}",
+                    url
+                )))
+        };
+
+        let state = Utc::now().timestamp_millis().to_string();
+        let mut auth_url = Url::parse(&format!("{}/oauth2/auth", ports.public_url()))
+            .map_err(|error| Error::Custom(format!("Invalid OAuth URL: {}", error)))?;
+        auth_url
+            .query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", OAUTH_CLIENT_ID)
+            .append_pair("redirect_uri", OAUTH_REDIRECT_URI)
+            .append_pair("scope", OAUTH_SCOPES)
+            .append_pair("state", &state);
+        let location = get(auth_url.as_str())?;
+
+        let login_challenge = query_param(&location, "login_challenge")?;
+        let login: RedirectResponse = agent
+            .put(&format!(
+                "{}/admin/oauth2/auth/requests/login/accept",
+                ports.admin_url()
+            ))
+            .query("login_challenge", &login_challenge)
+            .send_json(ureq::json!({ "subject": subject }))?
+            .into_json()?;
+        let location = get(&login.redirect_to)?;
+
+        let consent_challenge = query_param(&location, "consent_challenge")?;
+        let consent: RedirectResponse = agent
+            .put(&format!(
+                "{}/admin/oauth2/auth/requests/consent/accept",
+                ports.admin_url()
+            ))
+            .query("consent_challenge", &consent_challenge)
+            .send_json(ureq::json!({
+                "grant_scope": OAUTH_SCOPES.split(' ').collect::<Vec<&str>>()
+            }))?
+            .into_json()?;
+        let location = get(&consent.redirect_to)?;
+
+        let code = authorization_code(&location, &state)?;
+        let response: TokenResponse = agent
+            .post(&format!("{}/oauth2/token", ports.public_url()))
+            .send_form(&[
+                ("grant_type", "authorization_code"),
+                ("code", &code),
+                ("redirect_uri", OAUTH_REDIRECT_URI),
+                ("client_id", OAUTH_CLIENT_ID),
+            ])?
+            .into_json()?;
+        Ok(response.access_token)
+    })
+    .await?
+}
+
+/// Registers the public OAuth client of the CLI, if not already done
+fn register_client(agent: &ureq::Agent, ports: &OAuthPorts) -> Result<()> {
+    let result = agent
+        .post(&format!("{}/admin/clients", ports.admin_url()))
+        .send_json(ureq::json!({
+            "client_id": OAUTH_CLIENT_ID,
+            "client_name": "Lenra CLI",
+            "grant_types": ["authorization_code"],
+            "response_types": ["code"],
+            "redirect_uris": [OAUTH_REDIRECT_URI],
+            "scope": OAUTH_SCOPES,
+            "token_endpoint_auth_method": "none",
+        }));
+    match result {
+        // the client already exists
+        Ok(_) | Err(ureq::Error::Status(409, _)) => Ok(()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Revokes the OAuth login sessions of the given users, their browsers having to log in again
+pub async fn revoke_login_sessions(ports: OAuthPorts, users: Vec<DevtoolUser>) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        for user in users {
+            debug!("Revoke the login sessions of the user {}", user.email);
+            ureq::delete(&format!(
+                "{}/admin/oauth2/auth/sessions/login",
+                ports.admin_url()
+            ))
+            .query("subject", &user.id.to_string())
+            .call()?;
+        }
+        Ok(())
+    })
+    .await?
+}
+
+/// Makes an OAuth server URL relative to the host published one, the server only knowing its container address
+fn rebase_url(base: &str, url: &str) -> Result<String> {
+    let invalid = |error: url::ParseError| Error::Custom(format!("Invalid OAuth URL: {}", error));
+    let base = Url::parse(base).map_err(invalid)?;
+    let mut url = base.join(url).map_err(invalid)?;
+    url.set_host(base.host_str()).map_err(invalid)?;
+    url.set_port(base.port())
+        .map_err(|_| Error::Custom(format!("Invalid OAuth URL: {}", url)))?;
+    Ok(url.to_string())
+}
+
+/// Gets a query parameter of an OAuth server redirection, failing on the OAuth errors
+fn query_param(location: &str, name: &str) -> Result<String> {
+    let url = Url::parse(location)
+        .map_err(|error| Error::Custom(format!("Invalid OAuth redirection: {}", error)))?;
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    };
+    if let Some(error) = param("error") {
+        return Err(Error::Custom(format!(
+            "The devtool OAuth server refused the authorization: {}",
+            param("error_description").unwrap_or(error)
+        )));
+    }
+    param(name).ok_or(Error::Custom(format!(
+        "The OAuth redirection does not contain any {}",
+        name
+    )))
+}
+
+/// Extracts the authorization code from the redirection of the OAuth server
+fn authorization_code(location: &str, state: &str) -> Result<String> {
+    if query_param(location, "state")? != state {
+        return Err(Error::Custom("The OAuth state does not match".into()));
+    }
+    query_param(location, "code")
+}
+
+#[cfg(test)]
+mod test_users {
+    use super::*;
+
+    #[test]
+    fn queries() {
+        assert_eq!(
+            create_user_query("john@lenra.io").unwrap(),
+            "INSERT INTO users (email, inserted_at, updated_at) VALUES ('john@lenra.io', now(), now()) RETURNING id, email"
+        );
+        assert!(create_user_query("john").is_err());
+        assert!(create_user_query("john'); DROP TABLE users; --@lenra.io").is_err());
+    }
+
+    #[test]
+    fn users() {
+        assert_eq!(
+            parse_users("[{\"id\":1,\"email\":\"john@lenra.io\"}]\n").unwrap(),
+            vec![DevtoolUser {
+                id: 1,
+                email: "john@lenra.io".into()
+            }]
+        );
+        assert_eq!(parse_users("[]").unwrap(), vec![]);
+        assert!(parse_users("ERROR").is_err());
+    }
+
+    #[test]
+    fn code() {
+        assert_eq!(
+            authorization_code(
+                "http://localhost/lenra-cli/callback?code=abc&state=42",
+                "42"
+            )
+            .unwrap(),
+            "abc"
+        );
+        assert!(authorization_code(
+            "http://localhost/lenra-cli/callback?code=abc&state=43",
+            "42"
+        )
+        .is_err());
+        assert!(authorization_code(
+            "http://localhost/lenra-cli/callback?error=access_denied&state=42",
+            "42"
+        )
+        .is_err());
+        assert!(authorization_code("http://localhost/lenra-cli/callback?state=42", "42").is_err());
+    }
+
+    #[test]
+    fn challenges() {
+        assert_eq!(
+            query_param(
+                "http://localhost:4000/users/log_in?login_challenge=abc%3D",
+                "login_challenge"
+            )
+            .unwrap(),
+            "abc="
+        );
+        assert!(query_param("http://localhost:4000/users/log_in", "login_challenge").is_err());
+    }
+
+    #[test]
+    fn rebased_urls() {
+        assert_eq!(
+            rebase_url(
+                "http://localhost:14444",
+                "http://localhost:4444/oauth2/auth?login_verifier=abc"
+            )
+            .unwrap(),
+            "http://localhost:14444/oauth2/auth?login_verifier=abc"
+        );
+        assert_eq!(
+            rebase_url(
+                "http://localhost:14444",
+                "/oauth2/auth?consent_verifier=abc"
+            )
+            .unwrap(),
+            "http://localhost:14444/oauth2/auth?consent_verifier=abc"
+        );
+    }
+
+    #[test]
+    fn cookies() {
+        let mut cookies = Cookies::default();
+        cookies.store("oauth2_authentication_csrf=abc; Path=/; HttpOnly");
+        cookies.store("oauth2_consent_csrf=def; Path=/");
+        cookies.store("oauth2_authentication_csrf=ghi; Path=/");
+        assert_eq!(
+            cookies.header(),
+            "oauth2_consent_csrf=def; oauth2_authentication_csrf=ghi"
+        );
+    }
+
+    #[test]
+    fn token_response() {
+        let response: TokenResponse = serde_json::from_str(
+            r#"{"access_token":"ory_at_abc","expires_in":3599,"scope":"app:websocket","token_type":"bearer"}"#,
+        )
+        .unwrap();
+        assert_eq!(response.access_token, "ory_at_abc");
+    }
+}
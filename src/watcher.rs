@@ -0,0 +1,289 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    WalkBuilder,
+};
+use log::{debug, warn};
+use notify::{recommended_watcher, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver},
+    time::timeout,
+};
+
+use crate::errors::{Error, Result};
+
+/// The delay without any change before considering a burst of changes as ended
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
+
+/// Watches files and directories changes
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    receiver: UnboundedReceiver<notify::Result<Event>>,
+    /// The directories watched one by one
+    tree_dirs: HashSet<PathBuf>,
+}
+
+impl FileWatcher {
+    /// Watches the given paths recursively
+    pub fn new(paths: &[PathBuf]) -> Result<Self> {
+        let (sender, receiver) = unbounded_channel();
+        let mut watcher = recommended_watcher(move |event| {
+            sender.send(event).ok();
+        })?;
+        for path in paths {
+            debug!("Watch {:?}", path);
+            watcher.watch(path, RecursiveMode::Recursive)?;
+        }
+        Ok(FileWatcher {
+            watcher,
+            receiver,
+            tree_dirs: HashSet::new(),
+        })
+    }
+
+    /// Watches a single file.
+    /// Its parent directory is watched since editors often replace the file while saving it.
+    pub fn watch_file(&mut self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            debug!("Watch {:?}", path);
+            self.watcher.watch(parent, RecursiveMode::NonRecursive)?;
+        }
+        Ok(())
+    }
+
+    /// Watches the directories of a tree one by one, except the ignored ones and the excluded ones.
+    /// The ignored directories are never registered, which avoids exhausting the system watches on big dependencies directories.
+    pub fn watch_tree(
+        &mut self,
+        root: &Path,
+        rules: Arc<IgnoreRules>,
+        excluded: &[PathBuf],
+    ) -> Result<()> {
+        let excluded = excluded.to_vec();
+        let walker = WalkBuilder::new(root)
+            .standard_filters(false)
+            .filter_entry(move |entry| {
+                entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false)
+                    && !rules.is_ignored(entry.path())
+                    && !excluded.iter().any(|path| entry.path().starts_with(path))
+            })
+            .build();
+        for entry in walker {
+            let entry = entry.map_err(|error| Error::Custom(error.to_string()))?;
+            if self.tree_dirs.insert(entry.path().to_path_buf()) {
+                debug!("Watch {:?}", entry.path());
+                self.watcher
+                    .watch(entry.path(), RecursiveMode::NonRecursive)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_tree_dir(&self, path: &Path) -> bool {
+        self.tree_dirs.contains(path)
+    }
+
+    /// Waits for the next changes and returns the changed paths.
+    /// The changes happening in a burst are returned together.
+    pub async fn next_changes(&mut self) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        // wait for the first change
+        while paths.is_empty() {
+            let event = self
+                .receiver
+                .recv()
+                .await
+                .ok_or(Error::Custom("The file watcher has been stopped".into()))?;
+            add_changed_paths(&mut paths, event?);
+        }
+        // wait for the end of the burst
+        while let Ok(Some(event)) = timeout(DEBOUNCE_DELAY, self.receiver.recv()).await {
+            add_changed_paths(&mut paths, event?);
+        }
+        debug!("Changed paths: {:?}", paths);
+        Ok(paths)
+    }
+}
+
+/// The rules defining which changed files are ignored
+pub struct IgnoreRules {
+    root: PathBuf,
+    gitignore: Gitignore,
+    dockerignore: Vec<(GlobMatcher, bool)>,
+}
+
+impl IgnoreRules {
+    /// Loads the rules from the .gitignore file of the root directory and the given .dockerignore file.
+    /// The `.git` and `.lenra` directories are always ignored.
+    pub fn new(root: &Path, dockerignore: Option<&Path>) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        for always_ignored in [".git/", ".lenra/"] {
+            builder.add_line(None, always_ignored).ok();
+        }
+        if let Some(error) = builder.add(root.join(".gitignore")) {
+            debug!("No .gitignore file loaded: {}", error);
+        }
+        let gitignore = builder.build().unwrap_or_else(|error| {
+            warn!("Could not load the .gitignore rules: {}", error);
+            Gitignore::empty()
+        });
+        let dockerignore = dockerignore
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| parse_dockerignore(&content))
+            .unwrap_or_default();
+        IgnoreRules {
+            root: root.to_path_buf(),
+            gitignore,
+            dockerignore,
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let relative_path = match path.strip_prefix(&self.root) {
+            Ok(relative_path) => relative_path,
+            Err(_) => return false,
+        };
+        if self
+            .gitignore
+            .matched_path_or_any_parents(relative_path, path.is_dir())
+            .is_ignore()
+        {
+            return true;
+        }
+        // like Docker, the last pattern matching the path or one of its parents wins
+        let mut ignored = false;
+        for (matcher, negated) in &self.dockerignore {
+            if relative_path
+                .ancestors()
+                .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                .any(|ancestor| matcher.is_match(ancestor))
+            {
+                ignored = !negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn parse_dockerignore(content: &str) -> Vec<(GlobMatcher, bool)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (pattern, negated) = match line.strip_prefix('!') {
+                Some(pattern) => (pattern, true),
+                None => (line, false),
+            };
+            let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+            GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map(|glob| (glob.compile_matcher(), negated))
+                .map_err(|error| warn!("Invalid .dockerignore pattern {}: {}", line, error))
+                .ok()
+        })
+        .collect()
+}
+
+fn add_changed_paths(paths: &mut Vec<PathBuf>, event: Event) {
+    if event.kind.is_access() {
+        return;
+    }
+    event.paths.into_iter().for_each(|path| {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    });
+}
+
+#[cfg(test)]
+mod test_ignore_rules {
+    use std::{env, fs};
+
+    use super::*;
+
+    #[test]
+    fn gitignore_and_dockerignore() {
+        let dir = env::temp_dir().join("lenra_cli_ignore_rules");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".gitignore"), "node_modules\n*.log\n").unwrap();
+        let dockerignore = dir.join("Dockerfile.dockerignore");
+        fs::write(&dockerignore, "# generated\n**\n!/src\n/src/*.test.js\n").unwrap();
+        let rules = IgnoreRules::new(&dir, Some(&dockerignore));
+
+        assert!(!rules.is_ignored(&dir.join("src/index.js")));
+        assert!(!rules.is_ignored(&dir.join("src/lib/utils.js")));
+        assert!(rules.is_ignored(&dir.join("src/index.test.js")));
+        assert!(rules.is_ignored(&dir.join("README.md")));
+        assert!(rules.is_ignored(&dir.join("src/debug.log")));
+        assert!(rules.is_ignored(&dir.join("src/node_modules/lib/index.js")));
+        assert!(rules.is_ignored(&dir.join(".lenra/Dockerfile")));
+    }
+}
+
+#[cfg(test)]
+mod test_file_watcher {
+    use std::{env, fs, time::Duration};
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn burst_of_changes() {
+        let dir = env::temp_dir().join("lenra_cli_watcher");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let mut watcher = FileWatcher::new(&[dir.clone()]).unwrap();
+
+        fs::write(dir.join("first.txt"), "first").unwrap();
+        fs::write(dir.join("second.txt"), "second").unwrap();
+        let paths = timeout(Duration::from_secs(5), watcher.next_changes())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(paths.contains(&dir.join("first.txt")));
+        assert!(paths.contains(&dir.join("second.txt")));
+    }
+
+    #[tokio::test]
+    async fn ignored_directories_not_watched() {
+        let dir = env::temp_dir().join("lenra_cli_watch_tree");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(dir.join("src/lib")).unwrap();
+        fs::create_dir_all(dir.join("node_modules/lib")).unwrap();
+        fs::create_dir_all(dir.join("mounted/lib")).unwrap();
+        fs::write(dir.join(".gitignore"), "node_modules\n").unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let rules = Arc::new(IgnoreRules::new(&dir, None));
+        let mut watcher = FileWatcher::new(&[]).unwrap();
+        watcher
+            .watch_tree(&dir, rules, &[dir.join("mounted")])
+            .unwrap();
+
+        assert!(watcher.is_tree_dir(&dir));
+        assert!(watcher.is_tree_dir(&dir.join("src/lib")));
+        assert!(!watcher.is_tree_dir(&dir.join("node_modules")));
+        assert!(!watcher.is_tree_dir(&dir.join("node_modules/lib")));
+        assert!(!watcher.is_tree_dir(&dir.join("mounted")));
+        assert!(!watcher.is_tree_dir(&dir.join(".lenra")));
+
+        fs::write(dir.join("src/lib/index.js"), "").unwrap();
+        let paths = timeout(Duration::from_secs(5), watcher.next_changes())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(paths.contains(&dir.join("src/lib/index.js")));
+    }
+}